@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+/// Command aliases defined with `alias name = <command line>`.
+#[derive(Debug, Default)]
+pub struct Aliases {
+    aliases: BTreeMap<String, String>,
+}
+
+/// Substitute `$1`..`$9` in `body` with `args`. If the body has no positional
+/// parameters the arguments are appended instead.
+fn substitute(name: &str, body: &str, args: &[&str]) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut has_params = false;
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().and_then(|next| next.to_digit(10))) {
+            ('$', Some(digit)) if digit > 0 => {
+                chars.next();
+                has_params = true;
+                match args.get(digit as usize - 1) {
+                    Some(arg) => expanded.push_str(arg),
+                    None => {
+                        return Err(format!(
+                            "alias {} expects at least {} argument(s)",
+                            name, digit
+                        ))
+                    }
+                }
+            }
+            _ => expanded.push(c),
+        }
+    }
+    if !has_params {
+        for arg in args {
+            expanded.push(' ');
+            expanded.push_str(arg);
+        }
+    }
+    Ok(expanded)
+}
+
+impl Aliases {
+    pub fn new(aliases: BTreeMap<String, String>) -> Aliases {
+        Aliases { aliases }
+    }
+
+    pub fn as_map(&self) -> &BTreeMap<String, String> {
+        &self.aliases
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.aliases.keys().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.aliases.get(name)
+    }
+
+    pub fn insert(&mut self, name: String, body: String) {
+        self.aliases.insert(name, body);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.aliases.remove(name).is_some()
+    }
+
+    /// Expand the alias at the start of `line`, if there is one. As in a
+    /// shell, an alias is not expanded again on the same line, so that
+    /// `alias read = read -j 2` refers to the command.
+    pub fn expand(&self, line: &str) -> Result<String, String> {
+        let mut line = line.to_owned();
+        let mut expanded: Vec<String> = Vec::new();
+        loop {
+            let words: Vec<&str> = line.split_ascii_whitespace().collect();
+            let (name, body) = match words.first() {
+                Some(name) if expanded.iter().any(|done| done == name) => return Ok(line),
+                Some(name) => match self.aliases.get(*name) {
+                    Some(body) => (name.to_string(), body),
+                    None => return Ok(line),
+                },
+                None => return Ok(line),
+            };
+            line = substitute(&name, body, &words[1..])?;
+            expanded.push(name);
+        }
+    }
+
+    pub fn print(&self) {
+        for (name, body) in &self.aliases {
+            outln!("alias {} = {}", name, body);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(definitions: &[(&str, &str)]) -> Aliases {
+        Aliases::new(
+            definitions
+                .iter()
+                .map(|(name, body)| (name.to_string(), body.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn substitute_positional_parameters() {
+        assert_eq!(
+            substitute("orders", "read -d $1 -t json -r $2", &["shop", "current"]),
+            Ok("read -d shop -t json -r current".to_owned())
+        );
+    }
+
+    #[test]
+    fn substitute_appends_without_parameters() {
+        assert_eq!(
+            substitute("ls", "info --with-resources", &["-d", "shop"]),
+            Ok("info --with-resources -d shop".to_owned())
+        );
+    }
+
+    #[test]
+    fn substitute_missing_argument() {
+        assert_eq!(
+            substitute("orders", "read -d $1 -r $2", &["shop"]),
+            Err("alias orders expects at least 2 argument(s)".to_owned())
+        );
+    }
+
+    #[test]
+    fn expand_chained_aliases() {
+        let aliases = aliases(&[("ll", "ls --with-resources"), ("ls", "info")]);
+        assert_eq!(aliases.expand("ll"), Ok("info --with-resources".to_owned()));
+        assert_eq!(aliases.expand("read -j 1"), Ok("read -j 1".to_owned()));
+    }
+
+    #[test]
+    fn expand_alias_named_after_its_command() {
+        let aliases = aliases(&[("read", "read -j 2")]);
+        assert_eq!(aliases.expand("read -l 5"), Ok("read -j 2 -l 5".to_owned()));
+    }
+
+    #[test]
+    fn expand_alias_cycle_stops() {
+        let aliases = aliases(&[("a", "b 1"), ("b", "a 2")]);
+        assert_eq!(aliases.expand("a"), Ok("a 2 1".to_owned()));
+    }
+}
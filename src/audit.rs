@@ -0,0 +1,343 @@
+use chrono::{DateTime, FixedOffset, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::{
+    config::config_dir,
+    http::trace::command_status,
+    parsers::{get_server_string, AuditOpts, ContextStruct},
+    timestamp,
+};
+
+/// What a command changes.
+#[derive(Debug, Default)]
+pub struct Target {
+    pub database: Option<String>,
+    pub resource: Option<String>,
+    pub nodekey: Option<i128>,
+    /// The server changed, if it is not the one of the session.
+    pub server: Option<String>,
+}
+
+/// How an audited command ended, filled in by the command as it runs.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// The revision the command created, where it is known.
+    pub revision: Option<u64>,
+    /// The command failed, possibly without a failed response.
+    pub failed: bool,
+    /// The command changed nothing, so it is not logged.
+    pub skipped: bool,
+}
+
+/// Who changes data on which server, for scripts, which run without the
+/// shell context.
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub sirix_user: String,
+    pub server: String,
+}
+
+impl Session {
+    pub fn new(context: &ContextStruct) -> Self {
+        Session {
+            sirix_user: context.username.clone(),
+            server: get_server_string(context.context.clone()),
+        }
+    }
+}
+
+/// A line of the audit log.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Entry {
+    pub timestamp: String,
+    pub os_user: String,
+    pub sirix_user: String,
+    pub server: String,
+    pub command: String,
+    pub database: Option<String>,
+    pub resource: Option<String>,
+    pub nodekey: Option<i128>,
+    /// The status of the first failed response, or else of the last one,
+    /// or `None` if no request was sent.
+    pub status: Option<u16>,
+    /// Whether the command failed, which may also happen without a failed
+    /// response.
+    #[serde(default)]
+    pub failed: bool,
+    /// The revision created by the command, where the server reports it.
+    pub revision: Option<u64>,
+}
+
+fn audit_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("audit.jsonl"))
+}
+
+fn os_user() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+fn append(entry: &Entry) -> Result<(), String> {
+    let path = audit_path().ok_or("cannot determine the home directory")?;
+    let line = serde_json::to_string(entry).unwrap();
+    fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{}", line)
+        })
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
+/// Append a command which changed data to `~/.sirixsh/audit.jsonl`, unless
+/// its outcome says it was skipped.
+pub fn record(command: String, target: Target, context: &ContextStruct) -> Result<(), String> {
+    let outcome = &context.outcome;
+    if outcome.skipped {
+        return Ok(());
+    }
+    let session = Session::new(context);
+    let status = command_status();
+    append(&Entry {
+        timestamp: Local::now().to_rfc3339(),
+        os_user: os_user(),
+        sirix_user: session.sirix_user,
+        server: target.server.unwrap_or(session.server),
+        command,
+        database: target.database,
+        resource: target.resource,
+        nodekey: target.nodekey,
+        status,
+        failed: outcome.failed || status.is_some_and(|status| status >= 400),
+        revision: outcome.revision,
+    })
+}
+
+/// Append a change made by a script, with the status of its requests since
+/// `reset_status`.
+pub fn record_call(
+    session: &Session,
+    command: String,
+    target: Target,
+    failed: bool,
+) -> Result<(), String> {
+    append(&Entry {
+        timestamp: Local::now().to_rfc3339(),
+        os_user: os_user(),
+        sirix_user: session.sirix_user.clone(),
+        server: session.server.clone(),
+        command,
+        database: target.database,
+        resource: target.resource,
+        nodekey: target.nodekey,
+        status: command_status(),
+        failed,
+        revision: None,
+    })
+}
+
+fn matches(entry: &Entry, opts: &AuditOpts, since: Option<&DateTime<FixedOffset>>) -> bool {
+    let failed = entry.failed || entry.status.is_some_and(|status| status >= 400);
+    opts.database
+        .as_ref()
+        .is_none_or(|database| entry.database.as_ref() == Some(database))
+        && opts
+            .resource
+            .as_ref()
+            .is_none_or(|resource| entry.resource.as_ref() == Some(resource))
+        && opts
+            .user
+            .as_ref()
+            .is_none_or(|user| &entry.sirix_user == user || &entry.os_user == user)
+        && since.is_none_or(|since| {
+            timestamp::parse(&entry.timestamp, &Local).is_some_and(|at| at >= *since)
+        })
+        && (!opts.failed || failed)
+}
+
+/// The entries matching `opts`, only the last `--limit` of them. A `--since`
+/// without an offset is taken to be in local time, like the entries.
+fn select(entries: Vec<Entry>, opts: &AuditOpts) -> Result<Vec<Entry>, String> {
+    let since = match &opts.since {
+        Some(since) => Some(
+            timestamp::parse(since, &Local)
+                .ok_or_else(|| format!("invalid timestamp {}", since))?,
+        ),
+        None => None,
+    };
+    let mut entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|entry| matches(entry, opts, since.as_ref()))
+        .collect();
+    if let Some(limit) = opts.limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+    Ok(entries)
+}
+
+/// Print the entries of the audit log matching `opts`, returning them as
+/// a JSON array.
+pub fn query(opts: &AuditOpts) -> Result<Value, String> {
+    let path = audit_path().ok_or("cannot determine the home directory")?;
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    let entries = contents
+        .lines()
+        .enumerate()
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| format!("{}:{}: {}", path.display(), number + 1, err))
+        })
+        .collect::<Result<Vec<Entry>, String>>()?;
+    let entries = select(entries, opts)?;
+    for entry in &entries {
+        let status = match (entry.status, entry.failed) {
+            (Some(status), _) => status.to_string(),
+            (None, true) => "failed".to_owned(),
+            (None, false) => "-".to_owned(),
+        };
+        outln!(
+            "{} {}/{}@{} {} {}",
+            entry.timestamp,
+            entry.os_user,
+            entry.sirix_user,
+            entry.server,
+            status,
+            entry.command
+        );
+    }
+    Ok(serde_json::to_value(entries).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, database: &str, status: Option<u16>) -> Entry {
+        Entry {
+            timestamp: timestamp.to_owned(),
+            os_user: "alice".to_owned(),
+            sirix_user: "admin".to_owned(),
+            server: "localhost".to_owned(),
+            command: format!("delete database {}", database),
+            database: Some(database.to_owned()),
+            resource: None,
+            nodekey: None,
+            status,
+            failed: false,
+            revision: None,
+        }
+    }
+
+    fn opts() -> AuditOpts {
+        AuditOpts {
+            database: None,
+            resource: None,
+            user: None,
+            since: None,
+            failed: false,
+            limit: None,
+        }
+    }
+
+    fn databases(entries: Vec<Entry>, opts: &AuditOpts) -> Vec<String> {
+        select(entries, opts)
+            .unwrap()
+            .into_iter()
+            .filter_map(|entry| entry.database)
+            .collect()
+    }
+
+    #[test]
+    fn filters() {
+        let entries = || {
+            vec![
+                entry("2026-01-01T10:00:00+00:00", "orders", Some(200)),
+                entry("2026-01-01T11:00:00+00:00", "users", Some(404)),
+            ]
+        };
+        let by_database = AuditOpts {
+            database: Some("users".to_owned()),
+            ..opts()
+        };
+        assert_eq!(databases(entries(), &by_database), vec!["users"]);
+        let by_user = AuditOpts {
+            user: Some("bob".to_owned()),
+            ..opts()
+        };
+        assert!(databases(entries(), &by_user).is_empty());
+        let failed = AuditOpts {
+            failed: true,
+            ..opts()
+        };
+        assert_eq!(databases(entries(), &failed), vec!["users"]);
+    }
+
+    #[test]
+    fn failed_without_a_failed_response() {
+        let mut failed = entry("2026-01-01T10:00:00+00:00", "orders", Some(200));
+        failed.failed = true;
+        let opts = AuditOpts {
+            failed: true,
+            ..opts()
+        };
+        assert_eq!(databases(vec![failed], &opts), vec!["orders"]);
+    }
+
+    #[test]
+    fn since_compares_instants() {
+        let entries = vec![
+            entry("2026-03-01T11:30:00+00:00", "orders", None),
+            entry("2026-03-01T10:30:00+00:00", "users", None),
+        ];
+        // 11:30 UTC is after 12:00 at +01:00, though it sorts before it
+        let since = AuditOpts {
+            since: Some("2026-03-01T12:00:00+01:00".to_owned()),
+            ..opts()
+        };
+        assert_eq!(databases(entries, &since), vec!["orders"]);
+    }
+
+    #[test]
+    fn since_a_date() {
+        let entries = vec![
+            entry("2025-12-31T00:00:00+00:00", "orders", None),
+            entry("2026-01-02T00:00:00+05:00", "users", None),
+        ];
+        let since = AuditOpts {
+            since: Some("2026-01-01".to_owned()),
+            ..opts()
+        };
+        assert_eq!(databases(entries, &since), vec!["users"]);
+    }
+
+    #[test]
+    fn invalid_since() {
+        let since = AuditOpts {
+            since: Some("last week".to_owned()),
+            ..opts()
+        };
+        assert!(select(Vec::new(), &since).is_err());
+    }
+
+    #[test]
+    fn limit_keeps_the_last_entries() {
+        let entries = || {
+            ["a", "b", "c"]
+                .iter()
+                .map(|database| entry("2026-01-01T10:00:00+00:00", database, None))
+                .collect::<Vec<_>>()
+        };
+        let limit = |limit| AuditOpts {
+            limit: Some(limit),
+            ..opts()
+        };
+        assert_eq!(databases(entries(), &limit(2)), vec!["b", "c"]);
+        assert_eq!(databases(entries(), &limit(5)), vec!["a", "b", "c"]);
+        assert!(databases(entries(), &limit(0)).is_empty());
+    }
+}
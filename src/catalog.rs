@@ -0,0 +1,106 @@
+use serde_json::Value;
+use sirix_rust_client::{synchronous::resource::Resource, synchronous::sirix::Sirix};
+
+use crate::{
+    http::{
+        format_error, resource_history, sirix::server_info_with_resources, types::JsonResponse,
+    },
+    interrupt::interruptible,
+};
+
+/// A database on the server and its resources.
+#[derive(Debug, Clone)]
+pub struct DatabaseEntry {
+    pub name: String,
+    /// `json` or `xml`.
+    pub db_type: String,
+    pub resources: Vec<String>,
+}
+
+/// A revision from the history of a resource.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub number: u64,
+    pub timestamp: Option<String>,
+    pub author: Option<String>,
+    pub commit_message: Option<String>,
+}
+
+fn json_value(response: Option<JsonResponse>) -> Result<Value, String> {
+    match response {
+        Some(JsonResponse::Ok(value)) => Ok(value),
+        Some(JsonResponse::Err(err)) => Err(format_error(err)),
+        None => Err("request cancelled".to_owned()),
+    }
+}
+
+fn string_field(value: &Value, name: &str) -> Option<String> {
+    value.get(name).and_then(Value::as_str).map(str::to_owned)
+}
+
+/// Split `<database>/<resource>` into its parts; the resource is optional.
+pub fn split_target(target: &str) -> (String, Option<String>) {
+    match target.split_once('/') {
+        Some((database, resource)) if !resource.is_empty() => {
+            (database.to_owned(), Some(resource.to_owned()))
+        }
+        Some((database, _)) => (database.to_owned(), None),
+        None => (target.to_owned(), None),
+    }
+}
+
+/// The databases on the server with their resources.
+pub fn databases(sirix: Sirix) -> Result<Vec<DatabaseEntry>, String> {
+    let info = json_value(interruptible(move || server_info_with_resources(sirix)))?;
+    let databases = info
+        .get("databases")
+        .and_then(Value::as_array)
+        .ok_or("unexpected response to the server info")?;
+    Ok(databases
+        .iter()
+        .filter_map(|database| {
+            Some(DatabaseEntry {
+                name: string_field(database, "name")?,
+                db_type: string_field(database, "type")?.to_lowercase(),
+                resources: database
+                    .get("resources")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(str::to_owned)
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+/// Look up a database by name.
+pub fn database(sirix: Sirix, name: &str) -> Result<DatabaseEntry, String> {
+    databases(sirix)?
+        .into_iter()
+        .find(|database| database.name == name)
+        .ok_or_else(|| format!("database {} does not exist", name))
+}
+
+/// The revisions of a resource, oldest first.
+pub fn revisions<T: Send + 'static>(resource: Resource<T>) -> Result<Vec<Revision>, String> {
+    let history = json_value(interruptible(move || resource_history(resource)))?;
+    let entries = history
+        .get("history")
+        .and_then(Value::as_array)
+        .ok_or("unexpected response to the resource history")?;
+    let mut revisions: Vec<Revision> = entries
+        .iter()
+        .filter_map(|entry| {
+            Some(Revision {
+                number: entry.get("revision").and_then(Value::as_u64)?,
+                timestamp: string_field(entry, "revisionTimestamp"),
+                author: string_field(entry, "author"),
+                commit_message: string_field(entry, "commitMessage"),
+            })
+        })
+        .collect();
+    revisions.sort_by_key(|revision| revision.number);
+    Ok(revisions)
+}
@@ -0,0 +1,90 @@
+use clap::App;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hinter, HistoryHinter};
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::borrow::Cow;
+
+use crate::highlight::{highlight, paint, HINT};
+
+/// Line editor helper completing command and alias names, highlighting the
+/// line and hinting at matching history entries.
+pub struct ShellHelper {
+    commands: Vec<String>,
+    pub aliases: Vec<String>,
+    app: App<'static>,
+    hinter: HistoryHinter,
+}
+
+impl ShellHelper {
+    pub fn new(commands: Vec<String>, app: App<'static>) -> ShellHelper {
+        ShellHelper {
+            commands,
+            aliases: Vec::new(),
+            app,
+            hinter: HistoryHinter {},
+        }
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        // only the command itself is completed
+        if line.trim_start().contains(char::is_whitespace) {
+            return Ok((pos, Vec::new()));
+        }
+        let start = line.len() - line.trim_start().len();
+        let prefix = &line[start..];
+        let mut candidates: Vec<Pair> = self
+            .commands
+            .iter()
+            .chain(self.aliases.iter())
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair {
+                display: name.clone(),
+                replacement: format!("{} ", name),
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        candidates.dedup_by(|a, b| a.display == b.display);
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &Context<'_>) -> Option<String> {
+        self.hinter.hint(line, pos, ctx)
+    }
+}
+
+impl Highlighter for ShellHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned(highlight(line, &self.app, &self.commands, &self.aliases))
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        let mut painted = String::new();
+        paint(&mut painted, HINT, hint);
+        Cow::Owned(painted)
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        // every key can change whether a command or value is valid
+        true
+    }
+}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
@@ -0,0 +1,172 @@
+use serde::Deserialize;
+use serde_json::Value;
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{
+    convert::ConversionRules,
+    http::{build_agent, create_sirix, Connection, HttpOptions},
+};
+
+/// The server of a profile, connected with `Config::connect`.
+pub struct Server {
+    pub profile: String,
+    pub url: String,
+    pub sirix: Sirix,
+    pub connection: Connection,
+    pub production: bool,
+    pub read_only: bool,
+}
+
+impl Server {
+    /// Refuse to write to the server of a read-only profile, and point out
+    /// writes to a production one.
+    pub fn check_writable(&self) -> Result<(), String> {
+        if self.read_only {
+            return Err(format!(
+                "profile {} is read-only - remove `read_only` from the profile to write to it",
+                self.profile
+            ));
+        }
+        if self.production {
+            outln!(
+                "writing to {} of production profile {}",
+                self.url,
+                self.profile
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The shell configuration, read from `~/.sirixsh/config.json`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    /// The profile to use when none is given on the command line.
+    pub default_profile: Option<String>,
+    pub profiles: HashMap<String, Profile>,
+    /// The maximum number of commands kept in each profile's history.
+    pub history_size: Option<usize>,
+    /// Aliases defined with `alias`.
+    pub aliases: BTreeMap<String, String>,
+    /// The prompt template for profiles which don't set their own.
+    pub prompt: Option<String>,
+    /// How `--convert` maps documents between JSON and XML.
+    pub conversion: ConversionRules,
+}
+
+/// A named set of connection settings.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Profile {
+    pub url: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub http: HttpOptions,
+    /// The prompt template, see `set prompt`.
+    pub prompt: Option<String>,
+    /// Mark the prompt to warn that this is a production server.
+    pub production: bool,
+    /// Start in read-only mode, see `--read-only`.
+    pub read_only: bool,
+}
+
+/// The directory holding the configuration file and other shell state.
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".sirixsh"))
+}
+
+fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.json"))
+}
+
+/// Replace a single top level field of the configuration file, leaving the
+/// rest of the file as it is.
+pub fn save_field(name: &str, value: Value) -> Result<(), String> {
+    let path = config_path().ok_or("cannot determine the home directory")?;
+    let mut config = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("failed to parse {}: {}", path.display(), err))?,
+        Err(_) => Value::Object(Default::default()),
+    };
+    match config.as_object_mut() {
+        Some(object) => object.insert(name.to_owned(), value),
+        None => return Err(format!("{} is not a JSON object", path.display())),
+    };
+    let contents = serde_json::to_string_pretty(&config).unwrap();
+    fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| fs::write(&path, contents))
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
+impl Config {
+    /// Load the configuration file, falling back to an empty configuration
+    /// if it does not exist or cannot be parsed.
+    pub fn load() -> Config {
+        let path = match config_path() {
+            Some(path) => path,
+            None => return Config::default(),
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => return Config::default(),
+        };
+        match serde_json::from_str(&contents) {
+            Ok(config) => config,
+            Err(err) => {
+                outln!("failed to parse {}: {}", path.display(), err);
+                Config::default()
+            }
+        }
+    }
+
+    /// Look up a profile by name, or the default profile if no name is given.
+    pub fn profile(&self, name: Option<&str>) -> Option<(String, Profile)> {
+        let name = name.or(self.default_profile.as_deref())?;
+        match self.profiles.get(name) {
+            Some(profile) => Some((name.to_owned(), profile.clone())),
+            None => {
+                outln!("unknown profile {}", name);
+                None
+            }
+        }
+    }
+
+    /// Connect to the server of a profile without prompting, for commands
+    /// which talk to a second server. The password falls back to
+    /// `SIRIXSH_PASSWORD`.
+    pub fn connect(&self, name: &str) -> Result<Server, String> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| format!("unknown profile {}", name))?;
+        let url = profile
+            .url
+            .clone()
+            .ok_or_else(|| format!("profile {} has no url", name))?;
+        let username = profile
+            .username
+            .clone()
+            .ok_or_else(|| format!("profile {} has no username", name))?;
+        let password = profile
+            .password
+            .clone()
+            .or_else(|| env::var("SIRIXSH_PASSWORD").ok())
+            .ok_or_else(|| format!("profile {} has no password", name))?;
+        let agent = build_agent(&profile.http)?;
+        let connection = Connection::new(agent.clone(), &url, &username, &password);
+        let sirix = create_sirix(agent, &url, &username, &password);
+        Ok(Server {
+            profile: name.to_owned(),
+            url,
+            sirix,
+            connection,
+            production: profile.production,
+            read_only: profile.read_only,
+        })
+    }
+}
@@ -0,0 +1,365 @@
+use quick_xml::{
+    events::{BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// How documents are mapped between the JSON and XML models, configured in
+/// the `conversion` section of the configuration file.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ConversionRules {
+    /// Prefix of the JSON fields holding attributes.
+    pub attribute_prefix: String,
+    /// The JSON field holding the text of an element which also has
+    /// attributes or children.
+    pub text_key: String,
+    /// The root element for JSON documents which are not an object with a
+    /// single field.
+    pub root: String,
+    /// The element for the items of arrays which are not the value of a field.
+    pub item: String,
+    /// Elements which always become arrays, even if they occur only once.
+    pub arrays: Vec<String>,
+}
+
+impl Default for ConversionRules {
+    fn default() -> Self {
+        ConversionRules {
+            attribute_prefix: "@".to_owned(),
+            text_key: "#text".to_owned(),
+            root: "root".to_owned(),
+            item: "item".to_owned(),
+            arrays: Vec::new(),
+        }
+    }
+}
+
+/// An element being read, with the fields for its attributes and children.
+struct Frame {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+fn utf8(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn open<B: std::io::BufRead>(
+    reader: &Reader<B>,
+    start: &BytesStart,
+    rules: &ConversionRules,
+) -> Result<Frame, String> {
+    let mut fields = Map::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|err| err.to_string())?;
+        let value = attribute
+            .unescape_and_decode_value(reader)
+            .map_err(|err| err.to_string())?;
+        fields.insert(
+            format!("{}{}", rules.attribute_prefix, utf8(attribute.key)),
+            Value::String(value),
+        );
+    }
+    Ok(Frame {
+        name: utf8(start.name()),
+        fields,
+        text: String::new(),
+    })
+}
+
+/// The value of a finished element: its text if it has neither attributes
+/// nor children, `null` if it is empty, an object otherwise.
+fn close(frame: Frame, rules: &ConversionRules) -> Value {
+    match (frame.fields.is_empty(), frame.text.is_empty()) {
+        (true, true) => Value::Null,
+        (true, false) => Value::String(frame.text),
+        (false, empty) => {
+            let mut fields = frame.fields;
+            if !empty {
+                fields.insert(rules.text_key.clone(), Value::String(frame.text));
+            }
+            Value::Object(fields)
+        }
+    }
+}
+
+/// Add a child element to its parent, turning repeated elements into arrays.
+/// An empty element which always becomes an array is an empty array.
+fn insert(parent: &mut Map<String, Value>, name: String, value: Value, rules: &ConversionRules) {
+    match parent.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None if rules.arrays.contains(&name) => {
+            let items = match value {
+                Value::Null => Vec::new(),
+                value => vec![value],
+            };
+            parent.insert(name, Value::Array(items));
+        }
+        None => {
+            parent.insert(name, value);
+        }
+    }
+}
+
+/// Map an XML document to JSON: an object with a field for the root element.
+pub fn xml_to_json(xml: &str, rules: &ConversionRules) -> Result<Value, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut root = Map::new();
+    loop {
+        let finished = match reader.read_event(&mut buf) {
+            Ok(Event::Start(start)) => {
+                stack.push(open(&reader, &start, rules)?);
+                None
+            }
+            Ok(Event::Empty(start)) => Some(open(&reader, &start, rules)?),
+            Ok(Event::End(_)) => stack.pop(),
+            Ok(Event::Text(text)) => {
+                if let Some(frame) = stack.last_mut() {
+                    let text = text
+                        .unescape_and_decode(&reader)
+                        .map_err(|err| err.to_string())?;
+                    frame.text.push_str(&text);
+                }
+                None
+            }
+            Ok(Event::CData(text)) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&utf8(&text));
+                }
+                None
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => None,
+            Err(err) => {
+                return Err(format!(
+                    "invalid XML at position {}: {}",
+                    reader.buffer_position(),
+                    err
+                ))
+            }
+        };
+        if let Some(frame) = finished {
+            let name = frame.name.clone();
+            let value = close(frame, rules);
+            match stack.last_mut() {
+                Some(parent) => insert(&mut parent.fields, name, value, rules),
+                None => insert(&mut root, name, value, rules),
+            }
+        }
+        buf.clear();
+    }
+    Ok(Value::Object(root))
+}
+
+/// Replace the characters XML does not allow in element names.
+fn element_name(name: &str) -> String {
+    let mut element: String = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || "-_.:".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    if !element.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        element.insert(0, '_');
+    }
+    element
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn write_element<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    name: &str,
+    value: &Value,
+    rules: &ConversionRules,
+) -> quick_xml::Result<()> {
+    let name = element_name(name);
+    let mut start = BytesStart::owned_name(name.as_bytes());
+    let fields = match value {
+        // keep an empty array as an empty element rather than dropping it
+        Value::Array(items) if items.is_empty() => return writer.write_event(Event::Empty(start)),
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    // an array in an array needs an element of its own
+                    Value::Array(_) => {
+                        writer.write_event(Event::Start(start.to_borrowed()))?;
+                        write_element(writer, &rules.item, item, rules)?;
+                        writer.write_event(Event::End(BytesEnd::owned(name.clone().into())))?;
+                    }
+                    item => write_element(writer, &name, item, rules)?,
+                }
+            }
+            return Ok(());
+        }
+        Value::Null => return writer.write_event(Event::Empty(start)),
+        Value::Object(fields) => fields,
+        value => {
+            writer.write_event(Event::Start(start.to_borrowed()))?;
+            writer.write_event(Event::Text(BytesText::from_plain_str(&scalar(value))))?;
+            return writer.write_event(Event::End(BytesEnd::owned(name.into())));
+        }
+    };
+    let mut text = None;
+    let mut children = Vec::new();
+    for (key, value) in fields {
+        match key.strip_prefix(rules.attribute_prefix.as_str()) {
+            Some(attribute) if !rules.attribute_prefix.is_empty() && !value.is_object() => {
+                start.push_attribute((element_name(attribute).as_str(), scalar(value).as_str()))
+            }
+            _ if key == &rules.text_key => text = Some(scalar(value)),
+            _ => children.push((key, value)),
+        }
+    }
+    if text.is_none() && children.is_empty() {
+        return writer.write_event(Event::Empty(start));
+    }
+    writer.write_event(Event::Start(start.to_borrowed()))?;
+    if let Some(text) = &text {
+        writer.write_event(Event::Text(BytesText::from_plain_str(text)))?;
+    }
+    for (key, value) in children {
+        write_element(writer, key, value, rules)?;
+    }
+    writer.write_event(Event::End(BytesEnd::owned(name.into())))
+}
+
+/// Map a JSON document to XML. An object with a single field becomes the
+/// root element, anything else is wrapped in the configured root element.
+pub fn json_to_xml(value: &Value, rules: &ConversionRules) -> Result<String, String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    let result = match value {
+        Value::Object(fields) if fields.len() == 1 => {
+            let (name, value) = fields.iter().next().unwrap();
+            match value {
+                Value::Array(_) => {
+                    write_element(&mut writer, &rules.root, &value_of(name, value), rules)
+                }
+                value => write_element(&mut writer, name, value, rules),
+            }
+        }
+        Value::Array(_) => write_element(
+            &mut writer,
+            &rules.root,
+            &value_of(&rules.item, value),
+            rules,
+        ),
+        value => write_element(&mut writer, &rules.root, value, rules),
+    };
+    result.map_err(|err| err.to_string())?;
+    String::from_utf8(writer.into_inner()).map_err(|err| err.to_string())
+}
+
+fn value_of(name: &str, value: &Value) -> Value {
+    let mut fields = Map::new();
+    fields.insert(name.to_owned(), value.clone());
+    Value::Object(fields)
+}
+
+/// Convert the text of a document from one database type to another; a
+/// document of the requested type is returned as it is.
+pub fn convert(
+    data: &str,
+    from: &str,
+    to: &str,
+    rules: &ConversionRules,
+) -> Result<String, String> {
+    match (from, to) {
+        ("xml", "json") => Ok(serde_json::to_string_pretty(&xml_to_json(data, rules)?).unwrap()),
+        ("json", "xml") => {
+            let value =
+                serde_json::from_str(data).map_err(|err| format!("invalid JSON: {}", err))?;
+            json_to_xml(&value, rules)
+        }
+        _ => Ok(data.to_owned()),
+    }
+}
+
+/// A document as JSON, so that documents can be compared regardless of how
+/// they were formatted.
+pub fn normalize(data: &str, db_type: &str, rules: &ConversionRules) -> Result<Value, String> {
+    match db_type {
+        "xml" => xml_to_json(data, rules),
+        _ => serde_json::from_str(data).map_err(|err| format!("invalid JSON: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules(arrays: &[&str]) -> ConversionRules {
+        ConversionRules {
+            arrays: arrays.iter().map(|name| name.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn xml_attributes_and_text() {
+        let json = xml_to_json(r#"<book id="1" lang="en">Dune</book>"#, &rules(&[])).unwrap();
+        assert_eq!(
+            json,
+            json!({"book": {"@id": "1", "@lang": "en", "#text": "Dune"}})
+        );
+    }
+
+    #[test]
+    fn xml_repeated_elements_become_arrays() {
+        let xml = "<shelf><book>Dune</book><book>Emma</book><owner>Ann</owner></shelf>";
+        assert_eq!(
+            xml_to_json(xml, &rules(&[])).unwrap(),
+            json!({"shelf": {"book": ["Dune", "Emma"], "owner": "Ann"}})
+        );
+    }
+
+    #[test]
+    fn xml_arrays_rule() {
+        let xml = "<shelf><book>Dune</book><owner>Ann</owner></shelf>";
+        assert_eq!(
+            xml_to_json(xml, &rules(&["book"])).unwrap(),
+            json!({"shelf": {"book": ["Dune"], "owner": "Ann"}})
+        );
+    }
+
+    #[test]
+    fn json_attributes_and_text() {
+        let xml =
+            json_to_xml(&json!({"book": {"@id": "1", "#text": "Dune"}}), &rules(&[])).unwrap();
+        assert_eq!(xml, r#"<book id="1">Dune</book>"#);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let rules = rules(&["book"]);
+        let json = json!({"shelf": {"@id": "7", "book": [{"@id": "1", "#text": "Dune"}, "Emma"]}});
+        let xml = json_to_xml(&json, &rules).unwrap();
+        assert_eq!(xml_to_json(&xml, &rules).unwrap(), json);
+    }
+
+    #[test]
+    fn empty_arrays_are_kept() {
+        let rules = rules(&["tags"]);
+        let json = json!({"book": {"title": "Dune", "tags": []}});
+        let xml = json_to_xml(&json, &rules).unwrap();
+        assert!(xml.contains("<tags/>"), "{}", xml);
+        assert_eq!(xml_to_json(&xml, &rules).unwrap(), json);
+    }
+}
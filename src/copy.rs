@@ -0,0 +1,136 @@
+use sirix_rust_client::synchronous::sirix::Sirix;
+
+use crate::{
+    catalog,
+    config::Config,
+    convert::{convert, normalize},
+    export::{read_revision, resource_revisions},
+    http::Connection,
+    import::{commit, delete, original_message},
+    parsers::CopyOpts,
+};
+
+/// The content of the latest revision of a resource.
+fn latest(sirix: &Sirix, database: &str, resource: &str, db_type: &str) -> Result<String, String> {
+    let revisions = resource_revisions(sirix, database, resource, db_type)?;
+    match revisions.last() {
+        Some(revision) => read_revision(sirix, database, resource, db_type, revision.number),
+        None => Err(format!("{}/{} has no revisions", database, resource)),
+    }
+}
+
+/// Copy a resource to another database, possibly on another server, and
+/// with `--move` delete the source once the copy matches it. Returns the
+/// latest revision of the copy.
+pub fn copy(sirix: Sirix, connection: &Connection, opts: &CopyOpts) -> Result<Option<u64>, String> {
+    let (database, resource) = match catalog::split_target(&opts.source) {
+        (database, Some(resource)) => (database, resource),
+        _ => return Err("the source must be given as <database>/<resource>".to_owned()),
+    };
+    let (dst_database, dst_resource) = catalog::split_target(&opts.destination);
+    let dst_resource = dst_resource.unwrap_or_else(|| resource.clone());
+    let (dst_sirix, dst_connection) = match &opts.to {
+        Some(profile) => {
+            let server = Config::load().connect(profile)?;
+            server.check_writable()?;
+            (server.sirix, server.connection)
+        }
+        None if dst_database == database && dst_resource == resource => {
+            return Err("the source and destination are the same".to_owned())
+        }
+        None => (sirix.clone(), connection.clone()),
+    };
+    let entry = catalog::database(sirix.clone(), &database)?;
+    if !entry.resources.contains(&resource) {
+        return Err(format!("resource {}/{} does not exist", database, resource));
+    }
+    let db_type = opts
+        .convert
+        .clone()
+        .unwrap_or_else(|| entry.db_type.clone());
+    let rules = Config::load().conversion;
+    match catalog::databases(dst_sirix.clone())?
+        .into_iter()
+        .find(|dst| dst.name == dst_database)
+    {
+        Some(dst) if dst.db_type != db_type => {
+            return Err(format!(
+                "database {} is of type {}, the copy of type {}",
+                dst_database, dst.db_type, db_type
+            ))
+        }
+        Some(dst) if dst.resources.contains(&dst_resource) => {
+            return Err(format!(
+                "resource {}/{} already exists",
+                dst_database, dst_resource
+            ))
+        }
+        _ => {}
+    }
+    let mut revisions = resource_revisions(&sirix, &database, &resource, &entry.db_type)?;
+    if !opts.all_revisions {
+        revisions = revisions.into_iter().last().into_iter().collect();
+    }
+    if revisions.is_empty() {
+        return Err(format!("{}/{} has no revisions", database, resource));
+    }
+    for (i, revision) in revisions.iter().enumerate() {
+        let data = read_revision(
+            &sirix,
+            &database,
+            &resource,
+            &entry.db_type,
+            revision.number,
+        )?;
+        let data = convert(&data, &entry.db_type, &db_type, &rules)
+            .map_err(|err| format!("cannot convert revision {}: {}", revision.number, err))?;
+        let message = original_message(
+            revision.commit_message.as_deref(),
+            revision.number,
+            revision.author.as_deref(),
+            revision.timestamp.as_deref(),
+        );
+        commit(
+            &dst_connection,
+            &dst_database,
+            &dst_resource,
+            &db_type,
+            data,
+            i == 0,
+            Some(message),
+        )?;
+        outln!(
+            "copied {}/{} revision {} to {}/{}",
+            database,
+            resource,
+            revision.number,
+            dst_database,
+            dst_resource
+        );
+    }
+    let revision = resource_revisions(&dst_sirix, &dst_database, &dst_resource, &db_type)?
+        .last()
+        .map(|revision| revision.number);
+    if !opts.move_source {
+        return Ok(revision);
+    }
+    // the source may have changed while copying, so compare against the
+    // latest revision as it is now
+    let source = latest(&sirix, &database, &resource, &entry.db_type)?;
+    let source = convert(&source, &entry.db_type, &db_type, &rules)?;
+    let copy = latest(&dst_sirix, &dst_database, &dst_resource, &db_type)?;
+    // a converted document is formatted by the server, so compare the models
+    let same = match db_type == entry.db_type {
+        true => copy == source,
+        false => normalize(&copy, &db_type, &rules)? == normalize(&source, &db_type, &rules)?,
+    };
+    if !same {
+        return Err(format!(
+            "the copy {}/{} differs from the source, which was kept",
+            dst_database, dst_resource
+        ));
+    }
+    delete(&sirix, &database, &resource, &entry.db_type)?;
+    outln!("verified the copy and deleted {}/{}", database, resource);
+    Ok(revision)
+}
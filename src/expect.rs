@@ -0,0 +1,194 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::{
+    parsers::ContextStruct,
+    timing::{count_json_items, count_xml_items},
+    variables::lookup,
+};
+
+fn compare(actual: &Value, expected: &Value) -> Option<Ordering> {
+    match (actual, expected) {
+        (Value::Number(actual), Value::Number(expected)) => {
+            actual.as_f64()?.partial_cmp(&expected.as_f64()?)
+        }
+        (Value::String(actual), Value::String(expected)) => Some(actual.cmp(expected)),
+        _ if actual == expected => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn holds(actual: &Value, op: &str, expected: &Value) -> Result<bool, String> {
+    let ordering = compare(actual, expected);
+    Ok(match op {
+        "==" => ordering == Some(Ordering::Equal),
+        "!=" => ordering != Some(Ordering::Equal),
+        "<" => ordering == Some(Ordering::Less),
+        "<=" => matches!(ordering, Some(Ordering::Less) | Some(Ordering::Equal)),
+        ">" => ordering == Some(Ordering::Greater),
+        ">=" => matches!(ordering, Some(Ordering::Greater) | Some(Ordering::Equal)),
+        _ => return Err(format!("unknown operator {}", op)),
+    })
+}
+
+/// Parse the expected value as JSON, treating anything else as a string.
+fn parse_literal(words: &[String]) -> Value {
+    let literal = words.join(" ");
+    serde_json::from_str(&literal).unwrap_or(Value::String(literal))
+}
+
+/// Check a condition against the result of the last command, returning a
+/// description of the mismatch if it does not hold.
+pub fn check(condition: &[String], context: &ContextStruct) -> Result<(), String> {
+    evaluate(condition, context.last_error, context.variables.result(0))
+}
+
+/// Check a condition against the error status and result of the last command.
+fn evaluate(
+    condition: &[String],
+    last_error: Option<u16>,
+    result: Option<&Value>,
+) -> Result<(), String> {
+    let words: Vec<&str> = condition.iter().map(|word| word.as_str()).collect();
+    if let ["error", rest @ ..] = words.as_slice() {
+        return match (last_error, rest) {
+            (None, _) => Err("the last request succeeded".to_owned()),
+            (Some(_), []) => Ok(()),
+            (Some(status), [expected]) if status.to_string() == *expected => Ok(()),
+            (Some(status), [expected]) => {
+                Err(format!("expected error {}, got {}", expected, status))
+            }
+            _ => Err("usage: expect error [<status>]".to_owned()),
+        };
+    }
+    if let Some(status) = last_error {
+        return Err(format!("the last request failed with status {}", status));
+    }
+    let result = result.ok_or("there is no result to check")?;
+    let (subject, actual, op, expected) =
+        match words.as_slice() {
+            ["count", op, _, ..] => {
+                let count = match result {
+                    Value::String(xml) if xml.trim_start().starts_with('<') => count_xml_items(xml),
+                    _ => count_json_items(result),
+                };
+                (
+                    "count",
+                    Value::from(count),
+                    *op,
+                    parse_literal(&condition[2..]),
+                )
+            }
+            ["path", path, op, _, ..] => {
+                let path: Vec<&str> = path
+                    .trim_start_matches('.')
+                    .split('.')
+                    .filter(|key| !key.is_empty())
+                    .collect();
+                let actual = lookup(result, &path)
+                    .cloned()
+                    .ok_or_else(|| format!("{} not found in result", condition[1]))?;
+                (
+                    condition[1].as_str(),
+                    actual,
+                    *op,
+                    parse_literal(&condition[3..]),
+                )
+            }
+            _ => return Err(
+                "usage: expect count <op> <number> | path <path> <op> <json> | error [<status>]"
+                    .to_owned(),
+            ),
+        };
+    match holds(&actual, op, &expected)? {
+        true => Ok(()),
+        false => Err(format!(
+            "expected {} {} {}, got {}",
+            subject, op, expected, actual
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn words(condition: &str) -> Vec<String> {
+        condition.split(' ').map(|word| word.to_owned()).collect()
+    }
+
+    #[test]
+    fn literals_are_json_or_strings() {
+        assert_eq!(parse_literal(&words("42")), json!(42));
+        assert_eq!(parse_literal(&words("[1, 2]")), json!([1, 2]));
+        assert_eq!(parse_literal(&words("\"quoted\"")), json!("quoted"));
+        assert_eq!(parse_literal(&words("two words")), json!("two words"));
+    }
+
+    #[test]
+    fn operators_compare_numbers_and_strings() {
+        assert_eq!(holds(&json!(3), ">=", &json!(2.5)), Ok(true));
+        assert_eq!(holds(&json!(3), "<", &json!(3)), Ok(false));
+        assert_eq!(holds(&json!("abc"), "<", &json!("abd")), Ok(true));
+        assert_eq!(holds(&json!({"a": 1}), "==", &json!({"a": 1})), Ok(true));
+        assert_eq!(holds(&json!("1"), "==", &json!(1)), Ok(false));
+        assert_eq!(holds(&json!("1"), "!=", &json!(1)), Ok(true));
+    }
+
+    #[test]
+    fn count_and_path_conditions() {
+        let result = json!({"orders": [{"id": 1}, {"id": 2}], "total": 2});
+        assert_eq!(evaluate(&words("count == 2"), None, Some(&result)), Ok(()));
+        assert_eq!(
+            evaluate(&words("path .orders.1.id == 2"), None, Some(&result)),
+            Ok(())
+        );
+        assert_eq!(
+            evaluate(&words("path total > 5"), None, Some(&result)),
+            Err("expected total > 5, got 2".to_owned())
+        );
+        assert_eq!(
+            evaluate(&words("path missing == 1"), None, Some(&result)),
+            Err("missing not found in result".to_owned())
+        );
+    }
+
+    #[test]
+    fn error_conditions() {
+        assert_eq!(evaluate(&words("error"), Some(404), None), Ok(()));
+        assert_eq!(evaluate(&words("error 404"), Some(404), None), Ok(()));
+        assert_eq!(
+            evaluate(&words("error 500"), Some(404), None),
+            Err("expected error 500, got 404".to_owned())
+        );
+        assert_eq!(
+            evaluate(&words("error"), None, None),
+            Err("the last request succeeded".to_owned())
+        );
+        assert_eq!(
+            evaluate(&words("count == 1"), Some(404), None),
+            Err("the last request failed with status 404".to_owned())
+        );
+    }
+
+    #[test]
+    fn malformed_conditions() {
+        let result = json!([]);
+        assert!(evaluate(&words("count"), None, Some(&result))
+            .unwrap_err()
+            .starts_with("usage: expect"));
+        assert_eq!(
+            evaluate(&words("count == 0"), None, None),
+            Err("there is no result to check".to_owned())
+        );
+    }
+
+    #[test]
+    fn unknown_operator() {
+        assert_eq!(
+            holds(&json!(1), "=~", &json!(1)),
+            Err("unknown operator =~".to_owned())
+        );
+    }
+}
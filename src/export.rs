@@ -0,0 +1,275 @@
+use chrono::{Local, Utc};
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::fs;
+use std::path::Path;
+
+use crate::{
+    catalog::{self, Revision},
+    config::Config,
+    convert::convert,
+    http::{
+        format_error, read_json_resource, read_xml_resource,
+        types::{JsonResponse, XmlResponse},
+    },
+    interrupt::interruptible,
+    manifest::{Manifest, ResourceManifest, RevisionManifest},
+    parsers::{ExportOpts, RevisionType},
+    timestamp,
+};
+
+/// The revisions to export: all of them, those in `range`, or the latest.
+/// Timestamps without an offset are taken to be in UTC.
+fn select(
+    revisions: Vec<Revision>,
+    all: bool,
+    range: &Option<RevisionType>,
+) -> Result<Vec<Revision>, String> {
+    let parse = |value: &str| {
+        timestamp::parse(value, &Utc).ok_or_else(|| format!("invalid timestamp {}", value))
+    };
+    let at = |revision: &Revision| {
+        revision
+            .timestamp
+            .as_deref()
+            .and_then(|value| timestamp::parse(value, &Utc))
+    };
+    Ok(match range {
+        _ if all => revisions,
+        None => revisions.into_iter().last().into_iter().collect(),
+        Some(RevisionType::Revision { number, end_number }) => {
+            let end_number = end_number.unwrap_or(*number);
+            revisions
+                .into_iter()
+                .filter(|revision| (*number..=end_number).contains(&revision.number))
+                .collect()
+        }
+        Some(RevisionType::Timestamp {
+            timestamp: start,
+            end_timestamp: Some(end),
+        }) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            revisions
+                .into_iter()
+                .filter(|revision| at(revision).is_some_and(|at| start <= at && at <= end))
+                .collect()
+        }
+        // the revision which was current at the given time
+        Some(RevisionType::Timestamp {
+            timestamp: time,
+            end_timestamp: None,
+        }) => {
+            let time = parse(time)?;
+            revisions
+                .into_iter()
+                .rfind(|revision| at(revision).is_some_and(|at| at <= time))
+                .into_iter()
+                .collect()
+        }
+    })
+}
+
+/// Read a revision of a resource as the text to store for it.
+pub fn read_revision(
+    sirix: &Sirix,
+    database: &str,
+    resource: &str,
+    db_type: &str,
+    number: u64,
+) -> Result<String, String> {
+    let revision = Some(RevisionType::Revision {
+        number,
+        end_number: None,
+    });
+    match db_type {
+        "xml" => {
+            let resource = sirix
+                .xml_database(database.to_owned())
+                .resource(resource.to_owned());
+            match interruptible(move || {
+                read_xml_resource(resource, None, revision, None, None, None)
+            }) {
+                Some(XmlResponse::Ok(xml)) => Ok(xml),
+                Some(XmlResponse::Err(err)) => Err(format_error(err)),
+                None => Err("request cancelled".to_owned()),
+            }
+        }
+        _ => {
+            let resource = sirix
+                .json_database(database.to_owned())
+                .resource(resource.to_owned());
+            match interruptible(move || {
+                read_json_resource(resource, None, revision, None, None, None, None)
+            }) {
+                Some(JsonResponse::Ok(value)) => Ok(serde_json::to_string_pretty(&value).unwrap()),
+                Some(JsonResponse::Err(err)) => Err(format_error(err)),
+                None => Err("request cancelled".to_owned()),
+            }
+        }
+    }
+}
+
+/// The revisions of a resource of a database of the given type.
+pub fn resource_revisions(
+    sirix: &Sirix,
+    database: &str,
+    resource: &str,
+    db_type: &str,
+) -> Result<Vec<Revision>, String> {
+    match db_type {
+        "xml" => catalog::revisions(
+            sirix
+                .xml_database(database.to_owned())
+                .resource(resource.to_owned()),
+        ),
+        _ => catalog::revisions(
+            sirix
+                .json_database(database.to_owned())
+                .resource(resource.to_owned()),
+        ),
+    }
+}
+
+/// Write revisions of a database or resource to a directory, one file per
+/// resource and revision, together with a manifest describing them.
+pub fn export(sirix: Sirix, server: &str, opts: &ExportOpts) -> Result<(), String> {
+    if opts.all_revisions && opts.revision.is_some() {
+        return Err("--all-revisions cannot be combined with a revision".to_owned());
+    }
+    let (database, resource) = catalog::split_target(&opts.target);
+    let entry = catalog::database(sirix.clone(), &database)?;
+    let resources = match resource {
+        Some(resource) if entry.resources.contains(&resource) => vec![resource],
+        Some(resource) => return Err(format!("resource {}/{} does not exist", database, resource)),
+        None => entry.resources.clone(),
+    };
+    let db_type = opts
+        .convert
+        .clone()
+        .unwrap_or_else(|| entry.db_type.clone());
+    let rules = Config::load().conversion;
+    let dir = Path::new(&opts.dir);
+    fs::create_dir_all(dir).map_err(|err| format!("cannot create {}: {}", dir.display(), err))?;
+    let mut manifest = Manifest {
+        database: database.clone(),
+        db_type: db_type.clone(),
+        server: server.to_owned(),
+        exported: Local::now().to_rfc3339(),
+        resources: Vec::new(),
+    };
+    for name in resources {
+        let revisions = resource_revisions(&sirix, &database, &name, &entry.db_type)?;
+        let revisions = select(revisions, opts.all_revisions, &opts.revision)?;
+        if revisions.is_empty() {
+            outln!("no revisions of {}/{} to export", database, name);
+        }
+        fs::create_dir_all(dir.join(&name))
+            .map_err(|err| format!("cannot create {}: {}", dir.join(&name).display(), err))?;
+        let mut exported = Vec::new();
+        for revision in revisions {
+            let file = format!("{}/{}.{}", name, revision.number, db_type);
+            let contents =
+                read_revision(&sirix, &database, &name, &entry.db_type, revision.number)?;
+            let contents = convert(&contents, &entry.db_type, &db_type, &rules)
+                .map_err(|err| format!("cannot convert {}/{}: {}", name, revision.number, err))?;
+            fs::write(dir.join(&file), contents)
+                .map_err(|err| format!("cannot write {}: {}", dir.join(&file).display(), err))?;
+            outln!(
+                "exported {}/{} revision {}",
+                database,
+                name,
+                revision.number
+            );
+            exported.push(RevisionManifest {
+                revision: revision.number,
+                timestamp: revision.timestamp,
+                author: revision.author,
+                commit_message: revision.commit_message,
+                file,
+            });
+        }
+        manifest.resources.push(ResourceManifest {
+            name,
+            revisions: exported,
+        });
+    }
+    manifest.save(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn revisions() -> Vec<Revision> {
+        [
+            "2021-06-01T10:00:00Z",
+            "2021-06-02T10:00:00.250Z",
+            "2021-06-03T10:00:00Z",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(number, timestamp)| Revision {
+            number: number as u64 + 1,
+            timestamp: Some(timestamp.to_string()),
+            author: None,
+            commit_message: None,
+        })
+        .collect()
+    }
+
+    fn at(timestamp: &str) -> Option<RevisionType> {
+        Some(RevisionType::Timestamp {
+            timestamp: timestamp.to_owned(),
+            end_timestamp: None,
+        })
+    }
+
+    fn numbers(revisions: Result<Vec<Revision>, String>) -> Vec<u64> {
+        revisions
+            .unwrap()
+            .iter()
+            .map(|revision| revision.number)
+            .collect()
+    }
+
+    #[test]
+    fn revision_at_its_timestamp() {
+        let selected = select(revisions(), false, &at("2021-06-02T10:00:00.250Z"));
+        assert_eq!(numbers(selected), vec![2]);
+    }
+
+    #[test]
+    fn revision_between_timestamps() {
+        let selected = select(revisions(), false, &at("2021-06-02T12:00:00Z"));
+        assert_eq!(numbers(selected), vec![2]);
+    }
+
+    #[test]
+    fn no_revision_before_the_first() {
+        let selected = select(revisions(), false, &at("2021-05-31T10:00:00Z"));
+        assert!(numbers(selected).is_empty());
+    }
+
+    #[test]
+    fn other_offsets_and_precision() {
+        // 11:00 at +02:00 is before revision 2, though it sorts after it
+        let selected = select(revisions(), false, &at("2021-06-02T11:00:00+02:00"));
+        assert_eq!(numbers(selected), vec![1]);
+        // without fractional seconds, still before revision 2
+        let selected = select(revisions(), false, &at("2021-06-02T10:00:00Z"));
+        assert_eq!(numbers(selected), vec![1]);
+    }
+
+    #[test]
+    fn revisions_in_a_range() {
+        let range = Some(RevisionType::Timestamp {
+            timestamp: "2021-06-01T12:00:00+02:00".to_owned(),
+            end_timestamp: Some("2021-06-02".to_owned()),
+        });
+        assert_eq!(numbers(select(revisions(), false, &range)), vec![1]);
+    }
+
+    #[test]
+    fn invalid_timestamp() {
+        assert!(select(revisions(), false, &at("yesterday")).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use similar::TextDiff;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+use crate::{config::Config, parsers::StartupOpts};
+
+/// The extension of test scripts run by `sirixsh test`.
+const TEST_EXTENSION: &str = "sirixsh";
+
+fn test_files(dir: &str) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|err| format!("cannot read {}: {}", dir, err))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == TEST_EXTENSION))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Make sure the tests can connect without prompting, asking for the
+/// password once if it is needed.
+fn prepare_credentials(opts: &StartupOpts) -> Result<(), String> {
+    let config = Config::load();
+    let profile = config
+        .profile(opts.profile.as_deref())
+        .map(|(_, profile)| profile)
+        .unwrap_or_default();
+    if opts.url.is_none() && profile.url.is_none() {
+        return Err("tests need a server, set --url or use a profile with a url".to_owned());
+    }
+    if opts.username.is_none() && profile.username.is_none() {
+        return Err(
+            "tests need a user, set --username or use a profile with a username".to_owned(),
+        );
+    }
+    if env::var("SIRIXSH_PASSWORD").is_err() && profile.password.is_none() {
+        let mut rl = rustyline::Editor::<()>::new();
+        let password = rl
+            .readline_with_initial("password: ", ("admin", ""))
+            .map_err(|err| err.to_string())?;
+        env::set_var("SIRIXSH_PASSWORD", password);
+    }
+    Ok(())
+}
+
+/// Run a test, returning its output and whether all its expectations held.
+fn run_test(file: &Path, args: &[String]) -> Result<(String, bool), String> {
+    let exe = env::current_exe().map_err(|err| err.to_string())?;
+    let output = Command::new(exe)
+        .args(args)
+        .arg("run")
+        .arg(file)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("cannot run {}: {}", file.display(), err))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    Ok((stdout, output.status.success()))
+}
+
+/// Run every test in `dir`, print a report and exit with a failure status if
+/// a test failed.
+pub fn run_tests(opts: &StartupOpts, dir: &str, update: bool) {
+    let result = prepare_credentials(opts).and_then(|_| test_files(dir));
+    let files = match result {
+        Ok(files) => files,
+        Err(err) => {
+            println!("{}", err);
+            process::exit(2);
+        }
+    };
+    // every test is connected with the flags the shell was started with
+    let args = opts.args();
+    let mut failed = 0;
+    for file in &files {
+        let golden_path = file.with_extension("golden");
+        let (actual, expectations_held) = match run_test(file, &args) {
+            Ok(result) => result,
+            Err(err) => {
+                println!("FAIL {}: {}", file.display(), err);
+                failed += 1;
+                continue;
+            }
+        };
+        if update {
+            match fs::write(&golden_path, &actual) {
+                Ok(_) => println!("UPDATED {}", golden_path.display()),
+                Err(err) => {
+                    println!("FAIL {}: {}", golden_path.display(), err);
+                    failed += 1;
+                }
+            }
+            continue;
+        }
+        let expected = match fs::read_to_string(&golden_path) {
+            Ok(expected) => expected,
+            Err(_) => {
+                println!(
+                    "FAIL {}: no {}, run with --update to create it",
+                    file.display(),
+                    golden_path.display()
+                );
+                failed += 1;
+                continue;
+            }
+        };
+        if expected == actual && expectations_held {
+            println!("PASS {}", file.display());
+            continue;
+        }
+        failed += 1;
+        println!("FAIL {}", file.display());
+        if !expectations_held {
+            println!("  exited with a failure status");
+        }
+        if expected != actual {
+            let diff = TextDiff::from_lines(&expected, &actual);
+            print!(
+                "{}",
+                diff.unified_diff()
+                    .header(&golden_path.display().to_string(), "actual")
+            );
+        }
+    }
+    println!("{} passed, {} failed", files.len() - failed, failed);
+    if failed > 0 {
+        process::exit(1);
+    }
+}
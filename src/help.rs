@@ -0,0 +1,370 @@
+use clap::IntoApp;
+use sirix_rust_client::{synchronous::sirix::Sirix, types::DbType};
+
+use crate::{
+    alias::Aliases,
+    catalog,
+    parsers::{Context, ContextStruct},
+    Commands,
+};
+
+/// Example invocations shown by `help <command>`.
+const EXAMPLES: &[(&str, &[&str])] = &[
+    (
+        "context",
+        &[
+            "context database orders json",
+            "context resource current",
+            "context resource --database orders --type json current",
+            "context server",
+        ],
+    ),
+    (
+        "delete",
+        &[
+            "delete database orders",
+            "delete context server",
+        ],
+    ),
+    (
+        "read",
+        &[
+            "read --database orders --type json --resource current",
+            "read -d orders -t json -r current --max-depth 2 --limit 10",
+            "read -d orders -t json -r current --metadata=all revision 3",
+            "read -d orders -t xml -r current timestamp 2021-01-01T00:00:00",
+            "read -d legacy -t xml -r catalog --convert json",
+            "read -d orders -t json -r current --output current.json",
+            "read -d orders -t json -r current --page-size 20",
+            "read -d orders -t json -r current --chunk-size 1000 --output current.ndjson",
+        ],
+    ),
+    (
+        "info",
+        &[
+            "info",
+            "info --server --with-resources",
+            "info --database orders --type json",
+        ],
+    ),
+    (
+        "set",
+        &["set", "set timing", "set trace off", "set db orders"],
+    ),
+    (
+        "history-commands",
+        &["history-commands", "history-commands read"],
+    ),
+    ("source", &["source queries.sirixsh"]),
+    (
+        "alias",
+        &[
+            "alias",
+            "alias orders = read -d orders -t json -r $1",
+            "alias orders",
+        ],
+    ),
+    ("unalias", &["unalias orders"]),
+    ("script", &["script scripts/report.rhai"]),
+    (
+        "eval",
+        &["eval sirix.read(\"orders\", \"current\")", "eval 1 + 2"],
+    ),
+    (
+        "expect",
+        &[
+            "expect count == 3",
+            "expect path .status == \"ok\"",
+            "expect error 404",
+        ],
+    ),
+    (
+        "record",
+        &[
+            "record start session.md",
+            "record start session.json",
+            "record stop",
+        ],
+    ),
+    ("replay", &["replay session.json"]),
+    (
+        "export",
+        &[
+            "export orders backup",
+            "export orders/current backup --all-revisions",
+            "export orders/current backup revision 3 7",
+            "export legacy backup --convert json",
+        ],
+    ),
+    (
+        "load",
+        &[
+            "load data/*.json --database orders",
+            "load data/**/*.xml --database catalog --type xml --jobs 8",
+            "load data/*.json --database orders --resume",
+        ],
+    ),
+    (
+        "migrate",
+        &[
+            "migrate --from old --to new",
+            "migrate --from old --to new --databases orders-* --jobs 8",
+            "migrate --from old --to new --resume",
+        ],
+    ),
+    (
+        "copy",
+        &[
+            "copy orders/current archive/orders-2021",
+            "copy orders/current orders --to staging --all-revisions",
+            "copy orders/current orders/history --move",
+            "copy legacy/catalog catalog --convert json",
+        ],
+    ),
+    (
+        "replay-log",
+        &[
+            "replay-log states.ndjson --resource order-17",
+            "replay-log events.ndjson -r order-17 --mode merge-patch --data-field change --message-field event",
+            "replay-log patches.ndjson -r order-17 -d orders --mode json-patch",
+        ],
+    ),
+    (
+        "import",
+        &[
+            "import backup",
+            "import backup --database orders-copy",
+            "import backup --on-conflict suffix",
+        ],
+    ),
+    ("help", &["help", "help read"]),
+    ("unlock", &["unlock"]),
+    (
+        "audit",
+        &[
+            "audit",
+            "audit --failed --limit 20",
+            "audit --database orders --since 2026-01-01",
+            "audit --user alice --resource current",
+        ],
+    ),
+    ("next", &["read -d orders -t json -r current --page-size 20", "next"]),
+    ("prev", &["next", "prev"]),
+];
+
+fn command_names() -> Vec<String> {
+    Commands::into_app()
+        .get_subcommands()
+        .map(|command| command.get_name().to_owned())
+        .collect()
+}
+
+/// The candidate closest to `word`, if it is close enough to be a likely typo.
+pub fn suggest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = std::cmp::max(2, word.chars().count() / 3);
+    candidates
+        .into_iter()
+        .map(|candidate| (strsim::levenshtein(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Reject a line whose first word is neither a command nor an alias.
+pub fn check_command(name: &str, aliases: &Aliases) -> Result<(), String> {
+    let commands = command_names();
+    if name.starts_with('-') || commands.iter().any(|command| command == name) {
+        return Ok(());
+    }
+    let aliases = aliases.names();
+    let candidates = commands
+        .iter()
+        .chain(aliases.iter())
+        .map(|candidate| candidate.as_str());
+    match suggest(name, candidates) {
+        Some(suggestion) => Err(format!(
+            "unknown command `{}` - did you mean `{}`?",
+            name, suggestion
+        )),
+        None => Err(format!(
+            "unknown command `{}` - type `help` for a list of commands",
+            name
+        )),
+    }
+}
+
+fn type_name(db_type: &DbType) -> &'static str {
+    match db_type {
+        DbType::Json(_) => "json",
+        DbType::XML(_) => "xml",
+    }
+}
+
+/// Commands that can be run as they are in the current context.
+fn context_commands(context: &ContextStruct) -> Vec<String> {
+    let mut commands = match &context.context {
+        Context::Server(_) => vec![
+            "info".to_owned(),
+            "info --server --with-resources".to_owned(),
+            "context database <database> json|xml".to_owned(),
+            "delete context server".to_owned(),
+        ],
+        Context::Database {
+            database, db_type, ..
+        } => {
+            let db_type = type_name(db_type);
+            vec![
+                format!("info --database {} --type {}", database, db_type),
+                format!(
+                    "read --database {} --type {} --resource <resource>",
+                    database, db_type
+                ),
+                "context resource <resource>".to_owned(),
+                format!("delete database {}", database),
+                "context server".to_owned(),
+            ]
+        }
+        Context::Resource {
+            database,
+            db_type,
+            resource,
+            ..
+        } => {
+            let db_type = type_name(db_type);
+            vec![
+                format!(
+                    "read --database {} --type {} --resource {}",
+                    database, db_type, resource
+                ),
+                format!(
+                    "read --database {} --type {} --resource {} revision <revision>",
+                    database, db_type, resource
+                ),
+                format!("info --database {} --type {}", database, db_type),
+                format!("context database {} {}", database, db_type),
+            ]
+        }
+    };
+    if context.read_only {
+        commands.push("unlock".to_owned());
+    }
+    commands
+}
+
+fn print_overview(context: &ContextStruct) {
+    outln!("Commands:");
+    let app = Commands::into_app();
+    let commands: Vec<_> = app.get_subcommands().collect();
+    let width = commands
+        .iter()
+        .map(|command| command.get_name().len())
+        .max()
+        .unwrap_or(0);
+    for command in commands {
+        let about = command
+            .get_about()
+            .and_then(|about| about.lines().next())
+            .unwrap_or("");
+        outln!(
+            "    {:width$}  {}",
+            command.get_name(),
+            about,
+            width = width
+        );
+    }
+    outln!();
+    outln!("In the current context:");
+    for command in context_commands(context) {
+        outln!("    {}", command);
+    }
+    outln!();
+    outln!("Type `help <command>` for the options and examples of a command.");
+}
+
+/// Print the commands, or the options and examples of one command or alias.
+pub fn print(topic: Option<&str>, context: &ContextStruct) {
+    let name = match topic {
+        Some(name) => name,
+        None => return print_overview(context),
+    };
+    if let Some(body) = context.aliases.get(name) {
+        outln!("alias {} = {}", name, body);
+        return;
+    }
+    let app = Commands::into_app();
+    match app.find_subcommand(name) {
+        Some(command) => {
+            let mut help = Vec::new();
+            command.clone().write_long_help(&mut help).unwrap();
+            outln!("{}", String::from_utf8_lossy(&help).trim_end());
+            if let Some((_, examples)) = EXAMPLES.iter().find(|(command, _)| *command == name) {
+                outln!();
+                outln!("EXAMPLES:");
+                for example in examples.iter() {
+                    outln!("    {}", example);
+                }
+            }
+        }
+        None => {
+            if let Err(err) = check_command(name, &context.aliases) {
+                outln!("{}", err);
+            }
+        }
+    }
+}
+
+/// After a 404, suggest the existing database or resource closest to the
+/// one that was asked for.
+pub fn suggest_names(sirix: Sirix, database: &str, resource: Option<&str>) {
+    let databases = match catalog::databases(sirix) {
+        Ok(databases) => databases,
+        Err(_) => return,
+    };
+    match databases
+        .iter()
+        .find(|candidate| candidate.name == database)
+    {
+        Some(found) => {
+            let resource = match resource {
+                Some(resource) => resource,
+                None => return,
+            };
+            let resources = found.resources.iter().map(|resource| resource.as_str());
+            if let Some(suggestion) = suggest(resource, resources) {
+                outln!("did you mean resource `{}`?", suggestion);
+            }
+        }
+        None => {
+            let names = databases.iter().map(|database| database.name.as_str());
+            if let Some(suggestion) = suggest(database, names) {
+                outln!("did you mean database `{}`?", suggestion);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command;
+
+    #[test]
+    fn examples_parse() {
+        for (command, examples) in EXAMPLES {
+            for example in examples.iter() {
+                let words: Vec<&str> = example.split_ascii_whitespace().collect();
+                if let Err(err) = parse_command(&words) {
+                    panic!("help {}: `{}` does not parse: {}", command, example, err);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn examples_are_commands() {
+        let commands = command_names();
+        for (command, _) in EXAMPLES {
+            assert!(commands.iter().any(|name| name == command), "{}", command);
+        }
+    }
+}
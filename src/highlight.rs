@@ -0,0 +1,346 @@
+use clap::{App, Arg, ArgSettings};
+
+const RESET: &str = "\x1b[0m";
+const COMMAND: &str = "\x1b[1;34m";
+const ALIAS: &str = "\x1b[36m";
+const FLAG: &str = "\x1b[33m";
+const STRING: &str = "\x1b[32m";
+const KEYWORD: &str = "\x1b[1;35m";
+const INVALID: &str = "\x1b[31m";
+pub const HINT: &str = "\x1b[90m";
+
+/// JSONiq and XQuery keywords highlighted inside query strings.
+const KEYWORDS: &[&str] = &[
+    "and",
+    "as",
+    "ascending",
+    "at",
+    "by",
+    "case",
+    "cast",
+    "catch",
+    "count",
+    "declare",
+    "default",
+    "delete",
+    "descending",
+    "div",
+    "else",
+    "every",
+    "for",
+    "function",
+    "group",
+    "idiv",
+    "if",
+    "import",
+    "in",
+    "insert",
+    "instance",
+    "into",
+    "let",
+    "mod",
+    "module",
+    "namespace",
+    "not",
+    "of",
+    "or",
+    "order",
+    "rename",
+    "replace",
+    "return",
+    "satisfies",
+    "some",
+    "switch",
+    "then",
+    "to",
+    "treat",
+    "try",
+    "typeswitch",
+    "value",
+    "variable",
+    "where",
+    "with",
+];
+
+pub fn paint(out: &mut String, color: &str, text: &str) {
+    out.push_str(color);
+    out.push_str(text);
+    out.push_str(RESET);
+}
+
+/// The byte ranges of the whitespace separated words of `line`. Quoted
+/// strings are part of a word even if they contain whitespace.
+fn words(line: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c.is_whitespace() => {
+                if let Some(start) = start.take() {
+                    words.push((start, i));
+                }
+            }
+            None => {
+                if start.is_none() {
+                    start = Some(i);
+                }
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+            }
+        }
+    }
+    if let Some(start) = start {
+        words.push((start, line.len()));
+    }
+    words
+}
+
+/// Paint a query string, highlighting its keywords.
+fn paint_query(out: &mut String, text: &str) {
+    out.push_str(STRING);
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        let (word, tail) = match end {
+            0 => rest.split_at(rest.chars().next().unwrap().len_utf8()),
+            _ => rest.split_at(end),
+        };
+        if KEYWORDS.contains(&word) {
+            out.push_str(KEYWORD);
+            out.push_str(word);
+            out.push_str(STRING);
+        } else {
+            out.push_str(word);
+        }
+        rest = tail;
+    }
+    out.push_str(RESET);
+}
+
+/// Whether the string starting at `line[open]` is the query passed to
+/// `query(` in a script: its only argument, or the third one of
+/// `query(database, resource, query)`.
+fn is_query_string(line: &str, open: usize) -> bool {
+    let call = match line[..open].rfind("query(") {
+        Some(call) => call + "query(".len(),
+        None => return false,
+    };
+    let mut quote = None;
+    let mut commas = Vec::new();
+    for (i, c) in line[call..].char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                ',' => commas.push(call + i),
+                ')' if call + i < open => return false,
+                ')' => break,
+                _ => {}
+            },
+        }
+    }
+    let index = commas.iter().filter(|comma| **comma < open).count();
+    match commas.len() {
+        0 | 1 => index == 0,
+        _ => index == 2,
+    }
+}
+
+/// Paint the quoted strings in `line[start..end]`. The query passed to
+/// `query(` in a script gets its keywords highlighted as well.
+fn paint_strings(out: &mut String, line: &str, start: usize, end: usize) {
+    let mut pos = start;
+    while pos < end {
+        let text = &line[pos..end];
+        let open = match text.find(['"', '\'']) {
+            Some(open) => open,
+            None => {
+                out.push_str(text);
+                return;
+            }
+        };
+        out.push_str(&text[..open]);
+        let quote = text[open..].chars().next().unwrap();
+        let close = text[open + 1..]
+            .find(quote)
+            .map(|close| open + close + 2)
+            .unwrap_or_else(|| text.len());
+        match is_query_string(line, pos + open) {
+            true => paint_query(out, &text[open..close]),
+            false => paint(out, STRING, &text[open..close]),
+        }
+        pos += close;
+    }
+}
+
+fn find_flag<'a, 'help>(app: &'a App<'help>, flag: &str) -> Option<&'a Arg<'help>> {
+    match flag.strip_prefix("--") {
+        Some(long) => app.get_arguments().find(|arg| arg.get_long() == Some(long)),
+        None => {
+            let mut short = flag[1..].chars();
+            match (short.next(), short.next()) {
+                (Some(short), None) => app
+                    .get_arguments()
+                    .find(|arg| arg.get_short() == Some(short)),
+                _ => None,
+            }
+        }
+    }
+}
+
+fn is_valid(arg: &Arg, value: &str) -> bool {
+    match arg.get_possible_values() {
+        Some(values) => values.contains(&value),
+        None => true,
+    }
+}
+
+/// Color the command, its flags and quoted strings in `line`. Unknown
+/// commands and values which are not among an argument's possible values are
+/// marked in red. `commands` are the names accepted besides `app`'s
+/// subcommands, such as `exit`.
+pub fn highlight(line: &str, app: &App, commands: &[String], aliases: &[String]) -> String {
+    let mut out = String::with_capacity(line.len() * 2);
+    let mut last = 0;
+    let mut command = app;
+    let mut positional = 0;
+    let mut pending: Option<&Arg> = None;
+    for (i, (start, end)) in words(line).into_iter().enumerate() {
+        out.push_str(&line[last..start]);
+        last = end;
+        let word = &line[start..end];
+        if i == 0 {
+            if let Some(subcommand) = app.find_subcommand(word) {
+                command = subcommand;
+                paint(&mut out, COMMAND, word);
+            } else if commands.iter().any(|name| name == word) {
+                paint(&mut out, COMMAND, word);
+            } else if aliases.iter().any(|alias| alias == word) {
+                paint(&mut out, ALIAS, word);
+            } else if commands
+                .iter()
+                .chain(aliases.iter())
+                .any(|name| name.starts_with(word))
+                || word.starts_with('-')
+            {
+                // still being typed
+                out.push_str(word);
+            } else {
+                paint(&mut out, INVALID, word);
+            }
+            continue;
+        }
+        if let Some(arg) = pending.take() {
+            match is_valid(arg, word) {
+                true => paint_strings(&mut out, line, start, end),
+                false => paint(&mut out, INVALID, word),
+            }
+        } else if word.starts_with('-') && word.len() > 1 {
+            let (flag, value) = match word.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (word, None),
+            };
+            let arg = find_flag(command, flag);
+            paint(&mut out, FLAG, flag);
+            match (arg, value) {
+                (Some(arg), Some(value)) => {
+                    out.push('=');
+                    match is_valid(arg, value) {
+                        true => out.push_str(value),
+                        false => paint(&mut out, INVALID, value),
+                    }
+                }
+                (None, Some(value)) => {
+                    out.push('=');
+                    out.push_str(value);
+                }
+                (Some(arg), None) => {
+                    if arg.is_set(ArgSettings::TakesValue)
+                        && !arg.is_set(ArgSettings::RequireEquals)
+                    {
+                        pending = Some(arg);
+                    }
+                }
+                (None, None) => {}
+            }
+        } else if let Some(subcommand) = command.find_subcommand(word) {
+            command = subcommand;
+            positional = 0;
+            paint(&mut out, COMMAND, word);
+        } else {
+            let valid = command
+                .get_positionals()
+                .nth(positional)
+                .is_none_or(|arg| is_valid(arg, word));
+            positional += 1;
+            match valid {
+                true => paint_strings(&mut out, line, start, end),
+                false => paint(&mut out, INVALID, word),
+            }
+        }
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Commands;
+    use clap::IntoApp;
+
+    fn highlighted(line: &str) -> String {
+        highlight(line, &Commands::into_app(), &["exit".to_owned()], &[])
+    }
+
+    fn keyword(word: &str) -> String {
+        format!("{}{}{}", KEYWORD, word, STRING)
+    }
+
+    #[test]
+    fn keywords_in_query_strings() {
+        let line = highlighted("eval sirix.query(\"for $x in $doc return $x\")");
+        assert!(line.contains(&keyword("for")));
+        assert!(line.contains(&keyword("in")));
+        assert!(line.contains(&keyword("return")));
+        let line = highlighted("eval sirix.query(\"orders\", \"current\", 'let $x := 1')");
+        assert!(line.contains(&keyword("let")));
+    }
+
+    #[test]
+    fn only_the_query_of_three_arguments() {
+        let line = highlighted("eval sirix.query(\"for\", \"in\", \"for $x in $doc return $x\")");
+        assert!(line.contains(&format!("{}\"for\"{}", STRING, RESET)));
+        assert!(line.contains(&format!("{}\"in\"{}", STRING, RESET)));
+        assert!(line.contains(&keyword("return")));
+    }
+
+    #[test]
+    fn keywords_only_as_whole_words() {
+        let line = highlighted("eval sirix.query(\"format\")");
+        assert!(!line.contains(KEYWORD));
+        assert!(line.contains(&format!("{}\"format\"{}", STRING, RESET)));
+    }
+
+    #[test]
+    fn no_keywords_in_other_strings() {
+        let line = highlighted("eval sirix.read(\"for\", \"in\")");
+        assert!(!line.contains(KEYWORD));
+        assert!(line.contains(&format!("{}\"for\"{}", STRING, RESET)));
+        assert!(!highlighted("eval \"for $x in y return $x\"").contains(KEYWORD));
+    }
+
+    #[test]
+    fn no_keywords_in_arguments() {
+        let line = highlighted("read -d for -t json -r in");
+        assert!(!line.contains(KEYWORD));
+        assert!(line.contains(&format!("{}-d{} for", FLAG, RESET)));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::{config::config_dir, load::safe_name};
+
+/// Words that mark a line as containing a secret, which is never recorded.
+const SECRET_MARKERS: &[&str] = &["password", "passwd", "secret", "token"];
+
+/// The command history of a connection profile, persisted across sessions.
+pub struct CommandHistory {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    max_size: usize,
+    /// The number of lines in the history file, which is only trimmed once
+    /// it holds twice as many entries as are kept.
+    written: usize,
+}
+
+pub fn is_secret(line: &str) -> bool {
+    let line = line.to_lowercase();
+    SECRET_MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+impl CommandHistory {
+    /// Load the history of `profile` from `~/.sirixsh/history/<profile>`,
+    /// with any character other than letters, digits, `-` and `_` in the
+    /// profile name replaced.
+    pub fn load(profile: &str, max_size: usize) -> CommandHistory {
+        let path = config_dir().map(|dir| dir.join("history").join(safe_name(profile)));
+        let mut entries: Vec<String> = match &path {
+            Some(path) => fs::read_to_string(path)
+                .map(|contents| contents.lines().map(|line| line.to_owned()).collect())
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let written = entries.len();
+        if entries.len() > max_size {
+            entries.drain(..entries.len() - max_size);
+        }
+        CommandHistory {
+            entries,
+            path,
+            max_size,
+            written,
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Look up an entry by the 1-based number shown by `history-commands`.
+    pub fn get(&self, number: usize) -> Option<&String> {
+        number
+            .checked_sub(1)
+            .and_then(|index| self.entries.get(index))
+    }
+
+    /// Record a line, returning false if it was skipped because it is empty
+    /// or contains a secret.
+    pub fn add(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        if line.is_empty() || is_secret(line) {
+            return false;
+        }
+        self.entries.push(line.to_owned());
+        if self.entries.len() > self.max_size {
+            self.entries.remove(0);
+        }
+        if self.written >= self.max_size.max(1) * 2 {
+            self.save();
+        } else if let Some(path) = &self.path {
+            let appended = fs::create_dir_all(path.parent().unwrap()).and_then(|_| {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", line)
+            });
+            match appended {
+                Ok(()) => self.written += 1,
+                Err(err) => outln!("failed to write history to {}: {}", path.display(), err),
+            }
+        }
+        true
+    }
+
+    /// Rewrite the history file, which drops entries beyond the size limit.
+    fn save(&mut self) {
+        if let Some(path) = &self.path {
+            let mut contents = self.entries.join("\n");
+            contents.push('\n');
+            let saved =
+                fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(path, contents));
+            match saved {
+                Ok(()) => self.written = self.entries.len(),
+                Err(err) => outln!("failed to write history to {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    /// Print the entries containing `pattern`, numbered for use with `!n`.
+    pub fn print(&self, pattern: Option<&str>) {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if pattern.is_none_or(|pattern| entry.contains(pattern)) {
+                outln!("{:>5}  {}", index + 1, entry);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_detected_in_any_case() {
+        assert!(is_secret("set PASSWORD = hunter2"));
+        assert!(is_secret("eval sirix.login(\"admin\", \"Secret\")"));
+        assert!(is_secret("set api_token = abc"));
+        assert!(is_secret("passwd"));
+    }
+
+    #[test]
+    fn ordinary_lines_are_not_secret() {
+        assert!(!is_secret(
+            "read --database orders --type json --resource current"
+        ));
+        assert!(!is_secret("info --with-resources"));
+    }
+}
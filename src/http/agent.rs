@@ -0,0 +1,187 @@
+use serde::Deserialize;
+use sirix_rust_client::synchronous::error::{SirixError, SirixResult};
+use std::cell::Cell;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use ureq::{Middleware, MiddlewareNext, Request, Response};
+
+use rustls::{
+    client::{ServerCertVerified, ServerCertVerifier},
+    Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName,
+};
+
+use super::trace::{is_not_sent, trace_middleware};
+
+/// Settings for the HTTP agent, set in a profile or overridden by flags.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct HttpOptions {
+    /// Timeout in seconds for establishing a connection.
+    pub connect_timeout: Option<u64>,
+    /// Timeout in seconds for reading a response.
+    pub read_timeout: Option<u64>,
+    /// How often to retry idempotent requests after a transport error.
+    pub retries: Option<u32>,
+    /// HTTP(S) proxy to send all requests through.
+    pub proxy: Option<String>,
+    /// PEM file with additional trusted CA certificates.
+    pub ca_bundle: Option<String>,
+    /// PEM file with the client certificate chain.
+    pub client_cert: Option<String>,
+    /// PEM file with the private key for `client_cert`.
+    pub client_key: Option<String>,
+    /// Skip verification of the server certificate.
+    pub insecure: bool,
+}
+
+thread_local! {
+    /// The retry count of the agent which sent the last request on this
+    /// thread.
+    static AGENT_RETRIES: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Middleware passing the retry count of its agent to `with_retries`, which
+/// only sees the client types and not the agent sending the request.
+struct Retries(u32);
+
+impl Middleware for Retries {
+    fn handle(&self, request: Request, next: MiddlewareNext) -> Result<Response, ureq::Error> {
+        AGENT_RETRIES.with(|retries| retries.set(self.0));
+        next.handle(request)
+    }
+}
+
+/// Build the agent used for all requests to the server.
+pub fn build_agent(options: &HttpOptions) -> Result<ureq::Agent, String> {
+    let mut builder = ureq::AgentBuilder::new()
+        .middleware(Retries(options.retries.unwrap_or(0)))
+        .middleware(trace_middleware);
+    if let Some(seconds) = options.connect_timeout {
+        builder = builder.timeout_connect(Duration::from_secs(seconds));
+    }
+    if let Some(seconds) = options.read_timeout {
+        builder = builder.timeout_read(Duration::from_secs(seconds));
+    }
+    if let Some(proxy) = &options.proxy {
+        let proxy = ureq::Proxy::new(proxy).map_err(|err| format!("invalid proxy: {}", err))?;
+        builder = builder.proxy(proxy);
+    }
+    if options.ca_bundle.is_some()
+        || options.client_cert.is_some()
+        || options.client_key.is_some()
+        || options.insecure
+    {
+        builder = builder.tls_config(Arc::new(tls_config(options)?));
+    }
+    Ok(builder.build())
+}
+
+fn tls_config(options: &HttpOptions) -> Result<ClientConfig, String> {
+    let mut roots = RootCertStore::empty();
+    roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    if let Some(path) = &options.ca_bundle {
+        for cert in read_certs(path)? {
+            roots
+                .add(&cert)
+                .map_err(|err| format!("invalid certificate in {}: {}", path, err))?;
+        }
+    }
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let mut config = match (&options.client_cert, &options.client_key) {
+        (Some(cert), Some(key)) => builder
+            .with_single_cert(read_certs(cert)?, read_key(key)?)
+            .map_err(|err| format!("invalid client certificate: {}", err))?,
+        (Some(_), None) => return Err("--client-cert requires --client-key".to_owned()),
+        (None, Some(_)) => return Err("--client-key requires --client-cert".to_owned()),
+        (None, None) => builder.with_no_client_auth(),
+    };
+    if options.insecure {
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(NoVerification));
+    }
+    Ok(config)
+}
+
+fn read_certs(path: &str) -> Result<Vec<Certificate>, String> {
+    let file = File::open(path).map_err(|err| format!("cannot open {}: {}", path, err))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .map_err(|err| format!("cannot read {}: {}", path, err))?;
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn read_key(path: &str) -> Result<PrivateKey, String> {
+    let file = File::open(path).map_err(|err| format!("cannot open {}: {}", path, err))?;
+    let mut reader = BufReader::new(file);
+    loop {
+        match rustls_pemfile::read_one(&mut reader) {
+            Ok(Some(rustls_pemfile::Item::PKCS8Key(key)))
+            | Ok(Some(rustls_pemfile::Item::RSAKey(key))) => return Ok(PrivateKey(key)),
+            Ok(Some(_)) => continue,
+            Ok(None) => return Err(format!("no private key found in {}", path)),
+            Err(err) => return Err(format!("cannot read {}: {}", path, err)),
+        }
+    }
+}
+
+/// Accepts any server certificate, for `--insecure`.
+struct NoVerification;
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Run an idempotent request, retrying with exponential backoff on transport
+/// errors and on responses that indicate a temporarily unavailable server,
+/// as often as the agent sending it is configured to.
+pub fn with_retries<T, F>(mut request: F) -> SirixResult<T>
+where
+    F: FnMut() -> SirixResult<T>,
+{
+    AGENT_RETRIES.with(|retries| retries.set(0));
+    let mut attempt = 0;
+    loop {
+        match request() {
+            Err(err) if attempt < AGENT_RETRIES.with(Cell::get) && is_transient(&err) => {
+                thread::sleep(Duration::from_millis(200 << attempt.min(6)));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient(err: &SirixError) -> bool {
+    if is_not_sent(err) {
+        return false;
+    }
+    match err {
+        SirixError::ConnectionError(ureq::Error::Transport(_)) => true,
+        SirixError::ConnectionError(ureq::Error::Status(status, _)) => {
+            matches!(status, 502..=504)
+        }
+        _ => false,
+    }
+}
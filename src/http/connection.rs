@@ -0,0 +1,231 @@
+use serde_json::{json, Value};
+use sirix_rust_client::synchronous::error::{SirixError, SirixResult};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use crate::parsers::read::RevisionType;
+
+use super::{
+    error::format_error,
+    trace::{is_as_curl, set_body},
+};
+
+/// Talks to the REST API directly, for reads which must not be buffered, as
+/// the client library parses every response into memory, and for commits
+/// with a commit message, which the client library cannot send.
+#[derive(Clone)]
+pub struct Connection {
+    agent: ureq::Agent,
+    url: String,
+    username: String,
+    password: String,
+    token: Arc<Mutex<Option<String>>>,
+}
+
+/// The query of a streamed read, with the meaning of the `read` options.
+#[derive(Debug, Clone, Default)]
+pub struct StreamArgs {
+    pub node_id: Option<u128>,
+    pub revision: Option<RevisionType>,
+    pub max_level: Option<u64>,
+    pub top_level_limit: Option<u64>,
+    pub top_level_skip_last_node: Option<u64>,
+    /// `nodeKey`, `nodeKeyAndChildCount` or `true`.
+    pub metadata: Option<&'static str>,
+}
+
+impl StreamArgs {
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(node_id) = self.node_id {
+            query.push(("nodeId", node_id.to_string()));
+        }
+        match &self.revision {
+            Some(RevisionType::Revision {
+                number,
+                end_number: Some(end_number),
+            }) => {
+                query.push(("start-revision", number.to_string()));
+                query.push(("end-revision", end_number.to_string()));
+            }
+            Some(RevisionType::Revision { number, .. }) => {
+                query.push(("revision", number.to_string()))
+            }
+            Some(RevisionType::Timestamp {
+                timestamp,
+                end_timestamp: Some(end_timestamp),
+            }) => {
+                query.push(("start-revision-timestamp", timestamp.clone()));
+                query.push(("end-revision-timestamp", end_timestamp.clone()));
+            }
+            Some(RevisionType::Timestamp { timestamp, .. }) => {
+                query.push(("revision-timestamp", timestamp.clone()))
+            }
+            None => {}
+        }
+        if let Some(max_level) = self.max_level {
+            query.push(("maxLevel", max_level.to_string()));
+        }
+        if let Some(limit) = self.top_level_limit {
+            query.push(("nextTopLevelNodes", limit.to_string()));
+        }
+        if let Some(skip) = self.top_level_skip_last_node {
+            query.push(("lastTopLevelNodeKey", skip.to_string()));
+        }
+        if let Some(metadata) = self.metadata {
+            query.push(("withMetadata", metadata.to_owned()));
+        }
+        query
+    }
+}
+
+fn content_type(db_type: &str) -> &'static str {
+    match db_type {
+        "xml" => "application/xml",
+        _ => "application/json",
+    }
+}
+
+impl Connection {
+    pub fn new(agent: ureq::Agent, url: &str, username: &str, password: &str) -> Self {
+        Connection {
+            agent,
+            url: url.trim_end_matches('/').to_owned(),
+            username: username.to_owned(),
+            password: password.to_owned(),
+            token: Default::default(),
+        }
+    }
+
+    /// Fetch an access token, or reuse the one fetched before. With
+    /// `--as-curl` no token is fetched, so that the request asked for is
+    /// the one printed, which refers to `$SIRIX_TOKEN` anyway.
+    fn token(&self, renew: bool) -> SirixResult<String> {
+        if is_as_curl() {
+            return Ok("$SIRIX_TOKEN".to_owned());
+        }
+        let mut token = self.token.lock().unwrap();
+        if let (Some(token), false) = (token.as_ref(), renew) {
+            return Ok(token.clone());
+        }
+        let response = self
+            .agent
+            .post(&format!("{}/token", self.url))
+            .set("Content-Type", "application/json")
+            .send_string(
+                &json!({ "username": self.username, "password": self.password }).to_string(),
+            )
+            .map_err(SirixError::ConnectionError)?;
+        let body: Value = serde_json::from_reader(response.into_reader())
+            .map_err(|err| SirixError::FormatError(err.into()))?;
+        let access_token = body
+            .get("access_token")
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                SirixError::FormatError(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "no access token in the token response",
+                ))
+            })?
+            .to_owned();
+        *token = Some(access_token.clone());
+        Ok(access_token)
+    }
+
+    /// Send an authorized request to `/{database}/{resource}`, renewing an
+    /// expired token once.
+    fn send<F>(
+        &self,
+        method: &str,
+        database: &str,
+        resource: &str,
+        send: F,
+    ) -> SirixResult<ureq::Response>
+    where
+        F: Fn(ureq::Request) -> Result<ureq::Response, ureq::Error>,
+    {
+        let mut renew = false;
+        loop {
+            let request = self
+                .agent
+                .request(method, &format!("{}/{}/{}", self.url, database, resource))
+                .set("Authorization", &format!("Bearer {}", self.token(renew)?));
+            match send(request) {
+                Err(ureq::Error::Status(401, _)) if !renew => renew = true,
+                response => return response.map_err(SirixError::ConnectionError),
+            }
+        }
+    }
+
+    /// Start reading a resource, returning the response to copy the body
+    /// from as it arrives.
+    pub fn read(
+        &self,
+        database: &str,
+        resource: &str,
+        db_type: &str,
+        args: &StreamArgs,
+    ) -> Result<ureq::Response, String> {
+        self.send("GET", database, resource, |request| {
+            let mut request = request.set("Accept", content_type(db_type));
+            for (name, value) in args.query() {
+                request = request.query(name, &value);
+            }
+            request.call()
+        })
+        .map_err(format_error)
+    }
+
+    /// Create a resource with `data` as its first revision, returning the
+    /// body of the response.
+    pub fn create(
+        &self,
+        database: &str,
+        resource: &str,
+        db_type: &str,
+        data: &str,
+        commit_message: Option<&str>,
+    ) -> SirixResult<String> {
+        let response = self.send("PUT", database, resource, |request| {
+            let mut request = request.set("Content-Type", content_type(db_type));
+            if let Some(message) = commit_message {
+                request = request.query("commitMessage", message);
+            }
+            set_body(data);
+            request.send_string(data)
+        })?;
+        response.into_string().map_err(SirixError::FormatError)
+    }
+
+    /// Replace the whole document of a resource, committing it as a new
+    /// revision, and return the body of the response.
+    pub fn replace(
+        &self,
+        database: &str,
+        resource: &str,
+        db_type: &str,
+        data: &str,
+        commit_message: Option<&str>,
+    ) -> SirixResult<String> {
+        let response = self.send("HEAD", database, resource, |request| {
+            request
+                .set("Accept", content_type(db_type))
+                .query("nodeId", "1")
+                .call()
+        })?;
+        let etag = response.header("etag").unwrap_or_default().to_owned();
+        let response = self.send("POST", database, resource, |request| {
+            let mut request = request
+                .set("Content-Type", content_type(db_type))
+                .set("ETag", &etag)
+                .query("nodeId", "1")
+                .query("insert", "replace");
+            if let Some(message) = commit_message {
+                request = request.query("commitMessage", message);
+            }
+            set_body(data);
+            request.send_string(data)
+        })?;
+        response.into_string().map_err(SirixError::FormatError)
+    }
+}
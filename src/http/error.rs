@@ -1,17 +1,32 @@
 use sirix_rust_client::synchronous::error::SirixError;
 
-pub fn handle_error(err: SirixError) {
+use super::trace::is_not_sent;
+
+/// The HTTP status of a failed request, or `0` if there was no response.
+pub fn error_status(err: &SirixError) -> u16 {
+    match err {
+        SirixError::ConnectionError(ureq::Error::Status(status, _)) => *status,
+        _ => 0,
+    }
+}
+
+pub fn format_error(err: SirixError) -> String {
     match err {
         SirixError::ConnectionError(err) => match err {
-            ureq::Error::Transport(err) => {
-                println!("Transport error: {}", err);
-            }
-            ureq::Error::Status(status, response) => {
-                println!("Status: {}, {}", status, response.into_string().unwrap());
-            }
+            ureq::Error::Transport(err) => format!("Transport error: {}", err),
+            ureq::Error::Status(status, response) => format!(
+                "Status: {}, {}",
+                status,
+                response.into_string().unwrap_or_default()
+            ),
         },
-        SirixError::FormatError(err) => {
-            println!("Format error: {}", err);
-        }
+        SirixError::FormatError(err) => format!("Format error: {}", err),
+    }
+}
+
+pub fn handle_error(err: SirixError) {
+    if is_not_sent(&err) {
+        return;
     }
+    outln!("{}", format_error(err));
 }
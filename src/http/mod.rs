@@ -1,12 +1,21 @@
+pub mod agent;
+pub mod connection;
 pub mod database;
 pub mod error;
 pub mod format;
 pub mod resource;
 pub mod sirix;
+pub mod trace;
 pub mod types;
 
-pub use database::{database_info_json, database_info_xml, database_delete};
-pub use error::handle_error;
+pub use agent::{build_agent, HttpOptions};
+pub use connection::Connection;
+pub use database::{database_delete, database_info_json, database_info_xml};
+pub use error::{error_status, format_error, handle_error};
 pub use format::format_db_type;
-pub use resource::{read_json_resource, read_xml_resource};
-pub use sirix::{create_sirix, server_delete};
+pub use resource::{
+    create_json_resource, create_resource, create_xml_resource, diff_json_resource,
+    query_json_resource, read_json_resource, read_xml_resource, replace_resource, resource_delete,
+    resource_history, update_json_resource, update_xml_resource,
+};
+pub use sirix::{create_sirix, query, server_delete};
@@ -1,22 +1,20 @@
+use serde_json::json;
 use sirix_rust_client::{
-    synchronous::resource::Resource,
-    types::{Json, MetadataType, ReadArgs, RevisionArg, SingleRevision, TwoRevisions, Xml},
+    synchronous::{client::SirixResponse, error::SirixResult, resource::Resource},
+    types::{Insert, Json, MetadataType, ReadArgs, RevisionArg, SingleRevision, TwoRevisions, Xml},
 };
 
 use crate::parsers::read::RevisionType;
 
-use super::types::{JsonResponse, XmlResponse};
+use super::{
+    agent::with_retries,
+    connection::Connection,
+    trace::set_body,
+    types::{JsonResponse, XmlResponse},
+};
 
-pub fn read_json_resource(
-    resource: Resource<Json>,
-    node_id: Option<u128>,
-    revision: Option<RevisionType>,
-    max_level: Option<u64>,
-    top_level_limit: Option<u64>,
-    top_level_skip_last_node: Option<u64>,
-    metadata: Option<MetadataType>,
-) -> JsonResponse {
-    let revision = match revision {
+fn revision_arg(revision: &Option<RevisionType>) -> Option<RevisionArg> {
+    match revision.clone() {
         Some(rev) => match rev {
             RevisionType::Revision { number, end_number } => match end_number {
                 Some(end_number) => Some(RevisionArg::TwoRevisions(TwoRevisions::Number(
@@ -38,26 +36,31 @@ pub fn read_json_resource(
             },
         },
         None => None,
-    };
-    let response = match metadata {
-        Some(meta_type) => resource.read_with_metadata_raw(
-            meta_type,
-            ReadArgs {
-                node_id,
-                revision,
-                max_level,
-                top_level_limit,
-                top_level_skip_last_node,
-            },
-        ),
-        None => resource.read_raw(ReadArgs {
+    }
+}
+
+pub fn read_json_resource(
+    resource: Resource<Json>,
+    node_id: Option<u128>,
+    revision: Option<RevisionType>,
+    max_level: Option<u64>,
+    top_level_limit: Option<u64>,
+    top_level_skip_last_node: Option<u64>,
+    metadata: Option<MetadataType>,
+) -> JsonResponse {
+    let response = with_retries(|| {
+        let args = ReadArgs {
             node_id,
-            revision,
+            revision: revision_arg(&revision),
             max_level,
             top_level_limit,
             top_level_skip_last_node,
-        }),
-    };
+        };
+        match metadata.clone() {
+            Some(meta_type) => resource.read_with_metadata_raw(meta_type, args),
+            None => resource.read_raw(args),
+        }
+    });
     match response {
         Ok(response) => JsonResponse::Ok(response.body),
         Err(err) => JsonResponse::Err(err),
@@ -72,38 +75,154 @@ pub fn read_xml_resource(
     top_level_limit: Option<u64>,
     top_level_skip_last_node: Option<u64>,
 ) -> XmlResponse {
-    let revision = match revision {
-        Some(rev) => match rev {
-            RevisionType::Revision { number, end_number } => match end_number {
-                Some(end_number) => Some(RevisionArg::TwoRevisions(TwoRevisions::Number(
-                    number, end_number,
-                ))),
-                None => Some(RevisionArg::SingleRevision(SingleRevision::Number(number))),
-            },
-            RevisionType::Timestamp {
-                timestamp,
-                end_timestamp,
-            } => match end_timestamp {
-                Some(end_timestamp) => Some(RevisionArg::TwoRevisions(TwoRevisions::Timestamp(
-                    timestamp,
-                    end_timestamp,
-                ))),
-                None => Some(RevisionArg::SingleRevision(SingleRevision::Timestamp(
-                    timestamp,
-                ))),
-            },
-        },
-        None => None,
-    };
-    let response = resource.read_string(ReadArgs {
-        node_id,
-        revision,
-        max_level,
-        top_level_limit,
-        top_level_skip_last_node,
+    let response = with_retries(|| {
+        resource.read_string(ReadArgs {
+            node_id,
+            revision: revision_arg(&revision),
+            max_level,
+            top_level_limit,
+            top_level_skip_last_node,
+        })
     });
     match response {
         Ok(response) => XmlResponse::Ok(response.body),
         Err(err) => XmlResponse::Err(err),
     }
 }
+
+pub fn resource_history<T>(resource: Resource<T>) -> JsonResponse {
+    match with_retries(|| resource.history_raw()) {
+        Ok(response) => JsonResponse::Ok(response.body),
+        Err(err) => JsonResponse::Err(err),
+    }
+}
+
+pub fn create_json_resource(resource: Resource<Json>, data: String) -> JsonResponse {
+    set_body(&data);
+    match resource.create_raw(data) {
+        Ok(response) => JsonResponse::Ok(response.body),
+        Err(err) => JsonResponse::Err(err),
+    }
+}
+
+pub fn create_xml_resource(resource: Resource<Xml>, data: String) -> XmlResponse {
+    set_body(&data);
+    match resource.create_string(data) {
+        Ok(response) => XmlResponse::Ok(response.body),
+        Err(err) => XmlResponse::Err(err),
+    }
+}
+
+/// Create a resource with `data` as its first revision, committed with
+/// `commit_message`, which the client library has no parameter for.
+pub fn create_resource(
+    connection: &Connection,
+    database: &str,
+    resource: &str,
+    db_type: &str,
+    data: String,
+    commit_message: Option<String>,
+) -> SirixResult<String> {
+    connection.create(
+        database,
+        resource,
+        db_type,
+        &data,
+        commit_message.as_deref(),
+    )
+}
+
+pub fn update_json_resource(
+    resource: Resource<Json>,
+    node_id: u128,
+    data: String,
+    insert: Insert,
+    etag: Option<String>,
+) -> JsonResponse {
+    set_body(&data);
+    match resource.update_raw(node_id, data, insert, etag) {
+        Ok(response) => JsonResponse::Ok(response.body),
+        Err(err) => JsonResponse::Err(err),
+    }
+}
+
+pub fn update_xml_resource(
+    resource: Resource<Xml>,
+    node_id: u128,
+    data: String,
+    insert: Insert,
+    etag: Option<String>,
+) -> XmlResponse {
+    set_body(&data);
+    match resource.update_string(node_id, data, insert, etag) {
+        Ok(response) => XmlResponse::Ok(response.body),
+        Err(err) => XmlResponse::Err(err),
+    }
+}
+
+/// Replace the whole document of a resource, committing it as a new revision
+/// with `commit_message`.
+pub fn replace_resource(
+    connection: &Connection,
+    database: &str,
+    resource: &str,
+    db_type: &str,
+    data: String,
+    commit_message: Option<String>,
+) -> SirixResult<String> {
+    connection.replace(
+        database,
+        resource,
+        db_type,
+        &data,
+        commit_message.as_deref(),
+    )
+}
+
+pub fn resource_delete<T>(resource: Resource<T>) -> SirixResult<SirixResponse<()>> {
+    resource.delete(None, None)
+}
+
+pub fn diff_json_resource(
+    resource: Resource<Json>,
+    first_revision: u64,
+    second_revision: u64,
+    node_id: Option<u128>,
+    max_depth: Option<u64>,
+) -> JsonResponse {
+    let response =
+        with_retries(|| resource.diff_raw(first_revision, second_revision, node_id, max_depth));
+    match response {
+        Ok(response) => JsonResponse::Ok(response.body),
+        Err(err) => JsonResponse::Err(err),
+    }
+}
+
+/// The body the client library sends for a query, for `--as-curl`.
+pub fn query_body(
+    query: &str,
+    start_result_index: Option<u64>,
+    end_result_index: Option<u64>,
+) -> String {
+    let mut body = json!({ "query": query });
+    if let Some(start) = start_result_index {
+        body["startResultSeqIndex"] = json!(start);
+    }
+    if let Some(end) = end_result_index {
+        body["endResultSeqIndex"] = json!(end);
+    }
+    body.to_string()
+}
+
+pub fn query_json_resource(
+    resource: Resource<Json>,
+    query: String,
+    start_result_index: Option<u64>,
+    end_result_index: Option<u64>,
+) -> JsonResponse {
+    set_body(&query_body(&query, start_result_index, end_result_index));
+    match resource.query_raw(query, start_result_index, end_result_index) {
+        Ok(response) => JsonResponse::Ok(response.body),
+        Err(err) => JsonResponse::Err(err),
+    }
+}
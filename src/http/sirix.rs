@@ -3,23 +3,35 @@ use sirix_rust_client::synchronous::client::SirixResponse;
 use sirix_rust_client::synchronous::error::SirixResult;
 use sirix_rust_client::synchronous::sirix::Sirix;
 
-use super::types::JsonResponse;
+use super::{agent::with_retries, resource::query_body, trace::set_body, types::JsonResponse};
 
-pub fn create_sirix(base_url: &str, username: &str, password: &str) -> Sirix {
-    let agent = ureq::agent();
+pub fn create_sirix(agent: ureq::Agent, base_url: &str, username: &str, password: &str) -> Sirix {
     let lock = auth(agent.clone(), base_url, username, password);
     Sirix::new(base_url.to_string(), agent.clone(), Some(lock))
 }
 
 pub fn server_info(sirix: Sirix) -> JsonResponse {
-    match sirix.info_raw() {
+    match with_retries(|| sirix.info_raw()) {
         Ok(response) => JsonResponse::Ok(response.body),
         Err(err) => JsonResponse::Err(err),
     }
 }
 
 pub fn server_info_with_resources(sirix: Sirix) -> JsonResponse {
-    match sirix.info_with_resources_raw() {
+    match with_retries(|| sirix.info_with_resources_raw()) {
+        Ok(response) => JsonResponse::Ok(response.body),
+        Err(err) => JsonResponse::Err(err),
+    }
+}
+
+pub fn query(
+    sirix: Sirix,
+    query: String,
+    start_result_index: Option<u64>,
+    end_result_index: Option<u64>,
+) -> JsonResponse {
+    set_body(&query_body(&query, start_result_index, end_result_index));
+    match sirix.query_raw(query, start_result_index, end_result_index) {
         Ok(response) => JsonResponse::Ok(response.body),
         Err(err) => JsonResponse::Err(err),
     }
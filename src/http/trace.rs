@@ -0,0 +1,211 @@
+use sirix_rust_client::synchronous::error::SirixError;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use ureq::{MiddlewareNext, Request, Response};
+
+static TRACE: AtomicBool = AtomicBool::new(false);
+static AS_CURL: AtomicBool = AtomicBool::new(false);
+static ROUND_TRIPS: AtomicU64 = AtomicU64::new(0);
+static RECEIVED: AtomicU64 = AtomicU64::new(0);
+static UNSIZED: AtomicU64 = AtomicU64::new(0);
+static LAST_STATUS: AtomicU16 = AtomicU16::new(0);
+static FAILED_STATUS: AtomicU16 = AtomicU16::new(0);
+/// The body of the next request, for `--as-curl`, which middleware cannot
+/// read from the request itself.
+static BODY: Mutex<Option<String>> = Mutex::new(None);
+
+/// Log every request and its response to stderr.
+pub fn set_trace(enabled: bool) {
+    TRACE.store(enabled, Ordering::SeqCst);
+}
+
+/// Print requests as `curl` invocations instead of sending them.
+pub fn set_as_curl(enabled: bool) {
+    AS_CURL.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether requests are printed as `curl` invocations instead of sent.
+pub fn is_as_curl() -> bool {
+    AS_CURL.load(Ordering::SeqCst)
+}
+
+/// Remember the body of the request about to be sent, so that `--as-curl`
+/// can print it. Call it right before sending a request with a body.
+pub fn set_body(body: &str) {
+    if AS_CURL.load(Ordering::SeqCst) {
+        *BODY.lock().unwrap() = Some(body.to_owned());
+    }
+}
+
+/// The number of requests sent since the shell started.
+pub fn round_trips() -> u64 {
+    ROUND_TRIPS.load(Ordering::SeqCst)
+}
+
+/// The body bytes received since the shell started, going by the
+/// `Content-Length` of the responses, and the number of responses without
+/// one whose body was not counted with `count_body`.
+pub fn received() -> (u64, u64) {
+    (
+        RECEIVED.load(Ordering::SeqCst),
+        UNSIZED.load(Ordering::SeqCst),
+    )
+}
+
+/// Count the body of a response without a `Content-Length`, which was read
+/// by the shell itself.
+pub fn count_body(bytes: u64) {
+    RECEIVED.fetch_add(bytes, Ordering::SeqCst);
+    let _ = UNSIZED.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |unsized_| {
+        unsized_.checked_sub(1)
+    });
+}
+
+/// Forget the status of the last response, before running a command whose
+/// status is needed.
+pub fn reset_status() {
+    LAST_STATUS.store(0, Ordering::SeqCst);
+    FAILED_STATUS.store(0, Ordering::SeqCst);
+}
+
+/// The HTTP status of the last response since `reset_status`, if any.
+pub fn last_status() -> Option<u16> {
+    match LAST_STATUS.load(Ordering::SeqCst) {
+        0 => None,
+        status => Some(status),
+    }
+}
+
+/// The status of a command sending several requests since `reset_status`:
+/// that of the first failed response, or else of the last one. A `401`
+/// is not a failure, as the request is retried with a new token.
+pub fn command_status() -> Option<u16> {
+    match FAILED_STATUS.load(Ordering::SeqCst) {
+        0 => last_status(),
+        status => Some(status),
+    }
+}
+
+/// The error returned in place of a response when a request was printed as
+/// `curl` rather than sent.
+#[derive(Debug)]
+struct NotSent;
+
+impl fmt::Display for NotSent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "request not sent")
+    }
+}
+
+impl Error for NotSent {}
+
+/// Whether the request failed only because it was printed with `--as-curl`.
+pub fn is_not_sent(err: &SirixError) -> bool {
+    match err {
+        SirixError::ConnectionError(err) => err
+            .source()
+            .and_then(|source| source.downcast_ref::<io::Error>())
+            .and_then(|source| source.get_ref())
+            .is_some_and(|source| source.is::<NotSent>()),
+        _ => false,
+    }
+}
+
+fn is_secret(header: &str) -> bool {
+    header.eq_ignore_ascii_case("authorization") || header.eq_ignore_ascii_case("cookie")
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn as_curl(request: &Request) -> String {
+    let mut command = format!(
+        "curl -X {} {}",
+        request.method(),
+        shell_quote(request.url())
+    );
+    for name in request.header_names() {
+        if name.eq_ignore_ascii_case("authorization") {
+            command.push_str(" -H \"Authorization: Bearer $SIRIX_TOKEN\"");
+        } else if let Some(value) = request.header(&name) {
+            command.push_str(&format!(
+                " -H {}",
+                shell_quote(&format!("{}: {}", name, value))
+            ));
+        }
+    }
+    // a pending body belongs to the next request which can have one
+    if let "POST" | "PUT" = request.method() {
+        if let Some(body) = BODY.lock().unwrap().take() {
+            command.push_str(&format!(" --data-binary {}", shell_quote(&body)));
+        }
+    }
+    command
+}
+
+/// Middleware for the agent implementing `set trace on`, `--as-curl`, the
+/// round trip count for `set timing on` and the status for the audit log.
+pub fn trace_middleware(request: Request, next: MiddlewareNext) -> Result<Response, ureq::Error> {
+    if AS_CURL.load(Ordering::SeqCst) {
+        outln!("{}", as_curl(&request));
+        return Err(io::Error::other(NotSent).into());
+    }
+    ROUND_TRIPS.fetch_add(1, Ordering::SeqCst);
+    let trace = TRACE.load(Ordering::SeqCst);
+    if trace {
+        eprintln!("> {} {}", request.method(), request.url());
+        for name in request.header_names() {
+            match request.header(&name) {
+                Some(_) if is_secret(&name) => eprintln!("> {}: <redacted>", name),
+                Some(value) => eprintln!("> {}: {}", name, value),
+                None => {}
+            }
+        }
+    }
+    let start = Instant::now();
+    let result = next.handle(request);
+    let elapsed = start.elapsed();
+    let response = match &result {
+        Ok(response) => Some(response),
+        Err(ureq::Error::Status(_, response)) => Some(response),
+        Err(err) => {
+            if trace {
+                eprintln!("< failed after {:.1?}: {}", elapsed, err);
+            }
+            None
+        }
+    };
+    if let Some(response) = response {
+        LAST_STATUS.store(response.status(), Ordering::SeqCst);
+        if response.status() >= 400 && response.status() != 401 {
+            let _ = FAILED_STATUS.compare_exchange(
+                0,
+                response.status(),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+        }
+        match response
+            .header("content-length")
+            .and_then(|length| length.parse::<u64>().ok())
+        {
+            Some(length) => RECEIVED.fetch_add(length, Ordering::SeqCst),
+            None => UNSIZED.fetch_add(1, Ordering::SeqCst),
+        };
+        if trace {
+            eprintln!(
+                "< {} {} in {:.1?}, {} bytes",
+                response.status(),
+                response.status_text(),
+                elapsed,
+                response.header("content-length").unwrap_or("unknown")
+            );
+        }
+    }
+    result
+}
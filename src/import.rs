@@ -0,0 +1,259 @@
+use chrono::Local;
+use serde::Serialize;
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::fs;
+use std::path::Path;
+
+use crate::{
+    catalog,
+    export::resource_revisions,
+    http::{create_resource, format_error, replace_resource, resource_delete, Connection},
+    interrupt::interruptible,
+    manifest::{Manifest, RevisionManifest},
+    parsers::ImportOpts,
+};
+
+/// Written next to the manifest, recording which revision each exported
+/// revision became, with its original timestamp and commit message.
+const REPORT_FILE: &str = "import-report.json";
+
+#[derive(Serialize, Debug)]
+struct ImportReport {
+    server: String,
+    database: String,
+    imported: String,
+    resources: Vec<ResourceReport>,
+}
+
+#[derive(Serialize, Debug)]
+struct ResourceReport {
+    /// The name in the manifest.
+    original: String,
+    /// The name it was imported as.
+    name: String,
+    revisions: Vec<RevisionReport>,
+}
+
+#[derive(Serialize, Debug)]
+struct RevisionReport {
+    revision: Option<u64>,
+    original_revision: u64,
+    original_timestamp: Option<String>,
+    author: Option<String>,
+    commit_message: Option<String>,
+}
+
+/// The commit message for a revision recreated from another one. Sirix sets
+/// the author and timestamp of the new revision itself, so the original ones
+/// are kept in the message.
+pub fn original_message(
+    commit_message: Option<&str>,
+    revision: u64,
+    author: Option<&str>,
+    timestamp: Option<&str>,
+) -> String {
+    let mut origin = format!("originally revision {}", revision);
+    if let Some(author) = author {
+        origin.push_str(&format!(" by {}", author));
+    }
+    if let Some(timestamp) = timestamp {
+        origin.push_str(&format!(" at {}", timestamp));
+    }
+    match commit_message {
+        Some(message) if !message.is_empty() => format!("{} ({})", message, origin),
+        _ => origin,
+    }
+}
+
+/// Commit `data` to a resource with a commit message, creating the resource
+/// with the first revision.
+pub fn commit(
+    connection: &Connection,
+    database: &str,
+    resource: &str,
+    db_type: &str,
+    data: String,
+    create: bool,
+    commit_message: Option<String>,
+) -> Result<(), String> {
+    let connection = connection.clone();
+    let (database, resource, db_type) =
+        (database.to_owned(), resource.to_owned(), db_type.to_owned());
+    match interruptible(move || match create {
+        true => create_resource(
+            &connection,
+            &database,
+            &resource,
+            &db_type,
+            data,
+            commit_message,
+        ),
+        false => replace_resource(
+            &connection,
+            &database,
+            &resource,
+            &db_type,
+            data,
+            commit_message,
+        ),
+    }) {
+        Some(Ok(_)) => Ok(()),
+        Some(Err(err)) => Err(format_error(err)),
+        None => Err("request cancelled".to_owned()),
+    }
+}
+
+pub fn delete(sirix: &Sirix, database: &str, resource: &str, db_type: &str) -> Result<(), String> {
+    let (database, resource) = (database.to_owned(), resource.to_owned());
+    let sirix = sirix.clone();
+    let deleted = match db_type {
+        "xml" => {
+            interruptible(move || resource_delete(sirix.xml_database(database).resource(resource)))
+        }
+        _ => {
+            interruptible(move || resource_delete(sirix.json_database(database).resource(resource)))
+        }
+    };
+    match deleted {
+        Some(Ok(_)) => Ok(()),
+        Some(Err(err)) => Err(format_error(err)),
+        None => Err("request cancelled".to_owned()),
+    }
+}
+
+/// The first of `name-1`, `name-2`, ... which does not exist yet.
+fn with_suffix(name: &str, existing: &[String]) -> String {
+    (1..)
+        .map(|suffix| format!("{}-{}", name, suffix))
+        .find(|candidate| !existing.contains(candidate))
+        .unwrap()
+}
+
+fn replay(
+    connection: &Connection,
+    dir: &Path,
+    database: &str,
+    resource: &str,
+    db_type: &str,
+    revisions: &[RevisionManifest],
+) -> Result<(), String> {
+    for (i, revision) in revisions.iter().enumerate() {
+        let path = dir.join(&revision.file);
+        let data = fs::read_to_string(&path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        let message = original_message(
+            revision.commit_message.as_deref(),
+            revision.revision,
+            revision.author.as_deref(),
+            revision.timestamp.as_deref(),
+        );
+        commit(
+            connection,
+            database,
+            resource,
+            db_type,
+            data,
+            i == 0,
+            Some(message),
+        )?;
+        outln!(
+            "imported {}/{} revision {}{}",
+            database,
+            resource,
+            revision.revision,
+            revision
+                .timestamp
+                .as_ref()
+                .map_or(String::new(), |timestamp| format!(" from {}", timestamp))
+        );
+    }
+    Ok(())
+}
+
+/// Recreate the resources of a directory written by `export`, committing
+/// their revisions one after another. Returns the latest revision when a
+/// single resource was imported.
+pub fn import(
+    sirix: Sirix,
+    connection: &Connection,
+    server: &str,
+    opts: &ImportOpts,
+) -> Result<Option<u64>, String> {
+    let dir = Path::new(&opts.dir);
+    let manifest = Manifest::load(dir)?;
+    let database = opts
+        .database
+        .clone()
+        .unwrap_or_else(|| manifest.database.clone());
+    let mut existing = match catalog::databases(sirix.clone())?
+        .into_iter()
+        .find(|entry| entry.name == database)
+    {
+        Some(entry) if entry.db_type != manifest.db_type => {
+            return Err(format!(
+                "database {} is of type {}, the export of type {}",
+                database, entry.db_type, manifest.db_type
+            ))
+        }
+        Some(entry) => entry.resources,
+        None => Vec::new(),
+    };
+    let mut report = ImportReport {
+        server: server.to_owned(),
+        database: database.clone(),
+        imported: Local::now().to_rfc3339(),
+        resources: Vec::new(),
+    };
+    for resource in &manifest.resources {
+        let mut name = resource.name.clone();
+        if existing.contains(&name) {
+            match opts.on_conflict.as_str() {
+                "overwrite" => delete(&sirix, &database, &name, &manifest.db_type)?,
+                "suffix" => name = with_suffix(&name, &existing),
+                _ => {
+                    outln!("skipping {}/{}, which already exists", database, name);
+                    continue;
+                }
+            }
+        }
+        existing.push(name.clone());
+        replay(
+            connection,
+            dir,
+            &database,
+            &name,
+            &manifest.db_type,
+            &resource.revisions,
+        )?;
+        // the replayed revisions are the newest ones of the resource
+        let revisions = resource_revisions(&sirix, &database, &name, &manifest.db_type)?;
+        let first = revisions.len().saturating_sub(resource.revisions.len());
+        let mut numbers = revisions[first..].iter().map(|revision| revision.number);
+        report.resources.push(ResourceReport {
+            original: resource.name.clone(),
+            name,
+            revisions: resource
+                .revisions
+                .iter()
+                .map(|revision| RevisionReport {
+                    revision: numbers.next(),
+                    original_revision: revision.revision,
+                    original_timestamp: revision.timestamp.clone(),
+                    author: revision.author.clone(),
+                    commit_message: revision.commit_message.clone(),
+                })
+                .collect(),
+        });
+    }
+    let path = dir.join(REPORT_FILE);
+    fs::write(&path, serde_json::to_string_pretty(&report).unwrap())
+        .map_err(|err| format!("cannot write {}: {}", path.display(), err))?;
+    outln!("wrote {}", path.display());
+    match report.resources.as_slice() {
+        [resource] => Ok(resource
+            .revisions
+            .last()
+            .and_then(|revision| revision.revision)),
+        _ => Ok(None),
+    }
+}
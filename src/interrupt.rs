@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the SIGINT handler. While the line editor is reading input the
+/// terminal is in raw mode and Ctrl-C is reported by rustyline instead, so
+/// the handler only ever fires while a command is running.
+pub fn install_handler() {
+    if let Err(err) = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+        outln!("failed to install Ctrl-C handler: {}", err);
+    }
+}
+
+/// Forget a Ctrl-C pressed before a long running command started.
+pub fn reset() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// Whether Ctrl-C was pressed since the last check, for commands which
+/// stop between requests rather than cancelling them.
+pub fn interrupted() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// Run `task` on a worker thread, returning `None` if the user pressed Ctrl-C
+/// before it finished. The worker is detached rather than killed, so a
+/// cancelled request may still complete on the server. Off the main thread
+/// `task` just runs, leaving Ctrl-C to the command which started the thread.
+pub fn interruptible<T, F>(task: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    if thread::current().name() != Some("main") {
+        return Some(task());
+    }
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let _ = sender.send(task());
+    });
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(50)) {
+            Ok(result) => return Some(result),
+            Err(RecvTimeoutError::Timeout) => {
+                if INTERRUPTED.swap(false, Ordering::SeqCst) {
+                    outln!("request cancelled");
+                    return None;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
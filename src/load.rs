@@ -0,0 +1,288 @@
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    config::config_dir,
+    http::{
+        create_json_resource, create_xml_resource, format_error,
+        types::{JsonResponse, XmlResponse},
+    },
+    interrupt,
+    parsers::LoadOpts,
+};
+
+const BAR_WIDTH: usize = 30;
+
+/// `name` with every character other than letters, digits, `-` and `_`
+/// replaced by `-`, making it safe as a resource or file name.
+pub fn safe_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '-',
+        })
+        .collect()
+}
+
+/// The resource name for a file: its name without extension, with any
+/// character Sirix does not accept in names replaced by `-`.
+fn resource_name(path: &Path) -> String {
+    safe_name(
+        &path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default(),
+    )
+}
+
+/// Files which would become the same resource, e.g. `a b.json` and
+/// `a-b.json`, or `a.json` and `a.xml`.
+fn duplicate_names(files: &[PathBuf]) -> Vec<(String, Vec<&PathBuf>)> {
+    let mut names: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+    for path in files {
+        names.entry(resource_name(path)).or_default().push(path);
+    }
+    names
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect()
+}
+
+/// Where the files already loaded into a database of a server are
+/// remembered for `--resume`.
+fn state_path(server: &str, database: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| {
+        dir.join("load")
+            .join(safe_name(server))
+            .join(format!("{}.json", safe_name(database)))
+    })
+}
+
+fn load_state(server: &str, database: &str) -> BTreeSet<String> {
+    state_path(server, database)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(server: &str, database: &str, done: &BTreeSet<String>) -> Result<(), String> {
+    let path = state_path(server, database).ok_or("cannot determine the home directory")?;
+    fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| fs::write(&path, serde_json::to_string_pretty(done).unwrap()))
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
+fn file_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_owned())
+        .display()
+        .to_string()
+}
+
+/// Redraw the progress bar on stderr.
+pub fn progress(completed: usize, total: usize, failed: usize) {
+    let filled = match total {
+        0 => BAR_WIDTH,
+        _ => completed * BAR_WIDTH / total,
+    };
+    let failed = match failed {
+        0 => String::new(),
+        failed => format!(", {} failed", failed),
+    };
+    eprint!(
+        "\r[{}{}] {}/{}{}",
+        "#".repeat(filled),
+        ".".repeat(BAR_WIDTH - filled),
+        completed,
+        total,
+        failed
+    );
+    let _ = io::stderr().flush();
+}
+
+fn upload(sirix: &Sirix, database: &str, db_type: &str, path: &Path) -> Result<(), String> {
+    let data = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let name = resource_name(path);
+    match db_type {
+        "xml" => {
+            let resource = sirix.xml_database(database.to_owned()).resource(name);
+            match create_xml_resource(resource, data) {
+                XmlResponse::Ok(_) => Ok(()),
+                XmlResponse::Err(err) => Err(format_error(err)),
+            }
+        }
+        _ => {
+            let resource = sirix.json_database(database.to_owned()).resource(name);
+            match create_json_resource(resource, data) {
+                JsonResponse::Ok(_) => Ok(()),
+                JsonResponse::Err(err) => Err(format_error(err)),
+            }
+        }
+    }
+}
+
+/// Create a resource for every file matching a glob pattern, uploading
+/// several files at once. Ctrl-C stops after the running uploads.
+pub fn load(sirix: Sirix, server: &str, opts: &LoadOpts) -> Result<(), String> {
+    let paths = glob::glob(&opts.pattern)
+        .map_err(|err| format!("invalid pattern {}: {}", opts.pattern, err))?;
+    let mut files: Vec<PathBuf> = paths
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("no files match {}", opts.pattern));
+    }
+    let duplicates = duplicate_names(&files);
+    if !duplicates.is_empty() {
+        for (name, paths) in &duplicates {
+            let paths: Vec<String> = paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            outln!("{} would all become resource {}", paths.join(", "), name);
+        }
+        return Err("rename the files so that every resource name is unique".to_owned());
+    }
+    let mut done = match opts.resume {
+        true => load_state(server, &opts.database),
+        false => BTreeSet::new(),
+    };
+    let pending: Vec<PathBuf> = files
+        .iter()
+        .filter(|path| !done.contains(&file_key(path)))
+        .cloned()
+        .collect();
+    if pending.len() < files.len() {
+        outln!(
+            "skipping {} files loaded before",
+            files.len() - pending.len()
+        );
+    }
+    let total = pending.len();
+    let queue = Arc::new(Mutex::new(pending.into_iter()));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = channel();
+    for _ in 0..opts.jobs.max(1) {
+        let (queue, stop, sender) = (queue.clone(), stop.clone(), sender.clone());
+        let (sirix, database, db_type) = (sirix.clone(), opts.database.clone(), opts.type_.clone());
+        thread::spawn(move || loop {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let path = match queue.lock().unwrap().next() {
+                Some(path) => path,
+                None => break,
+            };
+            let result = upload(&sirix, &database, &db_type, &path);
+            if sender.send((path, result)).is_err() {
+                break;
+            }
+        });
+    }
+    drop(sender);
+    interrupt::reset();
+    let mut completed = 0;
+    let mut failures = Vec::new();
+    let mut checkpoint = Ok(());
+    progress(completed, total, failures.len());
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(50)) {
+            Ok((path, result)) => {
+                completed += 1;
+                match result {
+                    Ok(()) => {
+                        // checkpoint after every file, so a crash loses no
+                        // more than the running uploads
+                        done.insert(file_key(&path));
+                        if checkpoint.is_ok() {
+                            checkpoint = save_state(server, &opts.database, &done);
+                            if checkpoint.is_err() && !stop.swap(true, Ordering::SeqCst) {
+                                eprint!("\nstopping after the running uploads\n");
+                            }
+                        }
+                    }
+                    Err(err) => failures.push((path, err)),
+                }
+                progress(completed, total, failures.len());
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if interrupt::interrupted() && !stop.swap(true, Ordering::SeqCst) {
+                    eprint!("\nstopping after the running uploads\n");
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    eprintln!();
+    for (path, err) in &failures {
+        outln!("{}: {}", path.display(), err);
+    }
+    outln!(
+        "loaded {} of {} files into {}",
+        completed - failures.len(),
+        total,
+        opts.database
+    );
+    if completed < total || !failures.is_empty() {
+        outln!("run the same load with --resume to continue");
+    }
+    checkpoint?;
+    match failures.len() {
+        0 => Ok(()),
+        failed => Err(format!("{} of {} files failed", failed, total)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_name_replaces_other_characters() {
+        assert_eq!(safe_name("orders_2021-06"), "orders_2021-06");
+        assert_eq!(safe_name("a b.c/d"), "a-b-c-d");
+    }
+
+    #[test]
+    fn resource_name_drops_the_extension() {
+        assert_eq!(resource_name(Path::new("data/a b.json")), "a-b");
+    }
+
+    #[test]
+    fn duplicates_after_replacing_characters() {
+        let files = vec![PathBuf::from("a b.json"), PathBuf::from("a-b.json")];
+        let duplicates = duplicate_names(&files);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].0, "a-b");
+        assert_eq!(duplicates[0].1, vec![&files[0], &files[1]]);
+    }
+
+    #[test]
+    fn duplicates_with_different_extensions() {
+        let files = vec![
+            PathBuf::from("a.json"),
+            PathBuf::from("xml/a.xml"),
+            PathBuf::from("b.json"),
+        ];
+        let duplicates = duplicate_names(&files);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].0, "a");
+        assert_eq!(duplicates[0].1, vec![&files[0], &files[1]]);
+    }
+
+    #[test]
+    fn no_duplicates() {
+        let files = vec![PathBuf::from("a.json"), PathBuf::from("b.json")];
+        assert!(duplicate_names(&files).is_empty());
+    }
+}
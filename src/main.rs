@@ -1,73 +1,323 @@
+#[macro_use]
+mod output;
+
+mod alias;
+mod audit;
+mod catalog;
+mod completion;
+mod config;
+mod convert;
+mod copy;
+mod expect;
+mod export;
+mod golden;
+mod help;
+mod highlight;
+mod history;
 mod http;
+mod import;
+mod interrupt;
+mod load;
+mod manifest;
+mod metadata;
+mod migrate;
+mod paging;
 mod parsers;
+mod patch;
+mod prompt;
+mod record;
+mod replay_log;
+mod script;
+mod settings;
+mod source;
+mod stream;
+mod timestamp;
+mod timing;
+mod variables;
 
-use clap::Clap;
+use clap::{AppSettings, Clap, IntoApp};
 use http::{
-    create_sirix, database_delete, database_info_json, read_json_resource, read_xml_resource,
-    server_delete,
+    build_agent, create_sirix, database_delete, database_info_json, read_json_resource,
+    read_xml_resource, server_delete,
     sirix::{server_info, server_info_with_resources},
 };
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
-use serde_json::to_writer_pretty;
+use serde_json::{to_string_pretty, Value};
 use sirix_rust_client::{
     synchronous::sirix::Sirix,
     types::{DbType, Json, MetadataType, Xml},
 };
-use std::{error, fmt};
+use std::{env, error, fmt, path::Path, process, time::Instant};
 
 use crate::{
+    alias::Aliases,
+    completion::ShellHelper,
+    config::Config,
+    history::CommandHistory,
     http::{
-        database_info_xml, format_db_type, handle_error,
+        database_info_xml, error_status, handle_error,
+        trace::{is_not_sent, last_status, reset_status, set_as_curl},
         types::{JsonResponse, XmlResponse},
+        Connection,
     },
+    interrupt::interruptible,
     parsers::delete::{DeleteOptsImpl, DeleteScopeTypes},
+    record::Recorder,
+    settings::Settings,
 };
 
 #[derive(Clap, Debug)]
-#[clap(version = "0.1", author = "Moshe Uminer")]
+#[clap(
+    version = "0.1",
+    author = "Moshe Uminer",
+    setting = AppSettings::NoBinaryName,
+    setting = AppSettings::DisableHelpSubcommand
+)]
 enum Commands {
     Context(parsers::ContextOpts),
     Delete(parsers::DeleteOpts),
     Read(parsers::ReadOpts),
     Info(parsers::InfoOpts),
+    Set(parsers::SetOpts),
+    /// List previously entered commands - re-run one with `!<number>`.
+    HistoryCommands(parsers::HistoryCommandsOpts),
+    /// Run the commands in a file.
+    Source(parsers::SourceOpts),
+    /// Define an alias with `alias <name> = <command line>`, or list aliases.
+    Alias(parsers::AliasOpts),
+    /// Remove an alias.
+    Unalias(parsers::UnaliasOpts),
+    /// Run a Rhai script with access to the server through `sirix`.
+    Script(parsers::ScriptOpts),
+    /// Evaluate a Rhai expression and print its value.
+    Eval(parsers::EvalOpts),
+    /// Check the result of the last command, e.g. `expect count == 3`,
+    /// `expect path .status == "ok"` or `expect error 404`.
+    Expect(parsers::ExpectOpts),
+    /// Record commands and their output into a transcript.
+    Record(parsers::RecordOpts),
+    /// Run the commands of a JSON transcript.
+    Replay(parsers::ReplayOpts),
+    /// List the commands, or show the options and examples of one.
+    Help(parsers::HelpOpts),
+    /// Leave read-only mode for the rest of the session.
+    Unlock,
+    /// Show the page after the last read with `--page-size`.
+    Next,
+    /// Show the page before the last read with `--page-size`.
+    Prev,
+    /// Show the commands which changed data, from the audit log.
+    Audit(parsers::AuditOpts),
+    /// Write revisions of a database or resource to files with a manifest.
+    Export(parsers::ExportOpts),
+    /// Recreate the resources exported to a directory, revision by revision.
+    Import(parsers::ImportOpts),
+    /// Create a resource from every file matching a pattern.
+    Load(parsers::LoadOpts),
+    /// Commit each line of an NDJSON log as a new revision of a resource.
+    ReplayLog(parsers::ReplayLogOpts),
+    /// Copy a resource to another database or server, or move it with `--move`.
+    Copy(parsers::CopyOpts),
+    /// Copy every database with its history from one profile's server to another.
+    Migrate(parsers::MigrateOpts),
 }
 
 impl error::Error for Commands {}
 
+/// The server of a profile, for the audit log, or the profile name if it
+/// has no URL.
+fn profile_server(profile: &str) -> String {
+    Config::load()
+        .profiles
+        .get(profile)
+        .and_then(|profile| profile.url.clone())
+        .unwrap_or_else(|| profile.to_owned())
+}
+
+impl Commands {
+    /// Whether the command changes data on the server, which is not allowed
+    /// in read-only mode.
+    fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Commands::Delete(_)
+                | Commands::Import(_)
+                | Commands::Load(_)
+                | Commands::ReplayLog(_)
+                | Commands::Copy(_)
+                | Commands::Migrate(_)
+        )
+    }
+
+    /// What a mutating command changes, for the audit log.
+    fn target(&self, context: &parsers::Context) -> audit::Target {
+        let database = context.database().cloned();
+        let resource = context.resource().cloned();
+        match self {
+            Commands::Delete(opts) => match &opts.opts {
+                DeleteOptsImpl::Node(opts) => audit::Target {
+                    database: opts.database.clone().or(database),
+                    resource: opts.resource.clone().or(resource),
+                    nodekey: Some(opts.nodekey),
+                    ..Default::default()
+                },
+                DeleteOptsImpl::Scope(DeleteScopeTypes::Context(scope)) => match scope.opts {
+                    parsers::DeleteContextScopesImpl::Server => Default::default(),
+                    parsers::DeleteContextScopesImpl::Database => audit::Target {
+                        database,
+                        ..Default::default()
+                    },
+                    parsers::DeleteContextScopesImpl::Resource => audit::Target {
+                        database,
+                        resource,
+                        ..Default::default()
+                    },
+                },
+                DeleteOptsImpl::Scope(DeleteScopeTypes::Explicit(scope)) => match scope {
+                    parsers::DeleteExplicitScope::Database { database } => audit::Target {
+                        database: Some(database.clone()),
+                        ..Default::default()
+                    },
+                    parsers::DeleteExplicitScope::Resource { database, resource } => {
+                        audit::Target {
+                            database: Some(database.clone()),
+                            resource: Some(resource.clone()),
+                            ..Default::default()
+                        }
+                    }
+                },
+            },
+            Commands::Import(opts) => audit::Target {
+                database: opts.database.clone(),
+                ..Default::default()
+            },
+            Commands::Load(opts) => audit::Target {
+                database: Some(opts.database.clone()),
+                ..Default::default()
+            },
+            Commands::ReplayLog(opts) => audit::Target {
+                database: opts.database.clone().or(database),
+                resource: Some(opts.resource.clone()),
+                ..Default::default()
+            },
+            // a copy to another profile is recorded with its destination
+            Commands::Copy(opts) => match &opts.to {
+                Some(profile) => {
+                    let (database, resource) = catalog::split_target(&opts.destination);
+                    let (_, source) = catalog::split_target(&opts.source);
+                    audit::Target {
+                        database: Some(database),
+                        resource: resource.or(source),
+                        nodekey: None,
+                        server: Some(profile_server(profile)),
+                    }
+                }
+                None => {
+                    let (database, resource) = catalog::split_target(&opts.source);
+                    audit::Target {
+                        database: Some(database),
+                        resource,
+                        ..Default::default()
+                    }
+                }
+            },
+            Commands::Migrate(opts) => audit::Target {
+                server: Some(profile_server(&opts.to)),
+                ..Default::default()
+            },
+            _ => Default::default(),
+        }
+    }
+}
+
 impl fmt::Display for Commands {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Commands::Context(opts) => write!(f, "context {:?}", opts),
+            Commands::Context(opts) => write!(f, "context {}", opts),
             Commands::Delete(opts) => write!(f, "delete {}", opts),
             Commands::Read(opts) => write!(f, "read {}", opts),
             Commands::Info(opts) => write!(f, "info {}", opts),
+            Commands::Set(opts) => write!(f, "set {}", opts),
+            Commands::HistoryCommands(opts) => write!(f, "history-commands {}", opts),
+            Commands::Source(opts) => write!(f, "source {}", opts),
+            Commands::Alias(opts) => write!(f, "alias {}", opts),
+            Commands::Unalias(opts) => write!(f, "unalias {}", opts),
+            Commands::Script(opts) => write!(f, "script {}", opts),
+            Commands::Eval(opts) => write!(f, "eval {}", opts),
+            Commands::Expect(opts) => write!(f, "expect {}", opts),
+            Commands::Record(opts) => write!(f, "record {}", opts),
+            Commands::Replay(opts) => write!(f, "replay {}", opts),
+            Commands::Help(opts) => write!(f, "help {}", opts),
+            Commands::Unlock => write!(f, "unlock"),
+            Commands::Next => write!(f, "next"),
+            Commands::Prev => write!(f, "prev"),
+            Commands::Audit(opts) => write!(f, "audit {}", opts),
+            Commands::Export(opts) => write!(f, "export {}", opts),
+            Commands::Import(opts) => write!(f, "import {}", opts),
+            Commands::Load(opts) => write!(f, "load {}", opts),
+            Commands::ReplayLog(opts) => write!(f, "replay-log {}", opts),
+            Commands::Copy(opts) => write!(f, "copy {}", opts),
+            Commands::Migrate(opts) => write!(f, "migrate {}", opts),
         }
     }
 }
 
-fn handle_json_response(response: JsonResponse) {
+fn handle_json_response(response: JsonResponse, context: &mut parsers::ContextStruct) {
     match response {
         JsonResponse::Ok(response) => {
-            let _ = to_writer_pretty(std::io::stdout(), &response);
-            println!("");
+            timing::record_json(&response);
+            outln!("{}", to_string_pretty(&response).unwrap());
+            context.variables.push_result(response);
+            context.last_error = None;
+        }
+        JsonResponse::Err(err) => {
+            context.last_error = Some(error_status(&err));
+            handle_error(err);
         }
-        JsonResponse::Err(err) => handle_error(err),
     }
 }
 
-fn handle_xml_response(response: XmlResponse) {
+fn handle_xml_response(response: XmlResponse, context: &mut parsers::ContextStruct) {
     match response {
         XmlResponse::Ok(response) => {
-            println!("{}", response);
-            println!("");
+            timing::record_xml(&response);
+            outln!("{}", response);
+            outln!();
+            context.variables.push_result(Value::String(response));
+            context.last_error = None;
         }
         XmlResponse::Err(err) => {
+            context.last_error = Some(error_status(&err));
             handle_error(err);
         }
     }
 }
 
+/// Read and print a page, making it the current page if the read succeeds.
+fn show_page(sirix: Sirix, mut page: paging::Page, context: &mut parsers::ContextStruct) {
+    match paging::read(sirix, &mut page) {
+        // the page before was full, but the last one
+        Some((JsonResponse::Ok(_), 0)) if page.number() > 1 => {
+            if let Some(previous) = context.page.as_mut() {
+                previous.end();
+            }
+            outln!("no more pages - page {} was the last", page.number() - 1);
+        }
+        Some((response, _)) => {
+            handle_json_response(response, context);
+            if context.last_error.is_none() {
+                outln!(
+                    "page {} - `next` and `prev` to turn the page",
+                    page.number()
+                );
+                context.page = Some(page);
+            }
+        }
+        None => {}
+    }
+}
+
 fn execute_command(command: Commands, sirix: Sirix, context: &mut parsers::ContextStruct) {
     match command {
         Commands::Context(opts) => match opts.opts {
@@ -111,16 +361,41 @@ fn execute_command(command: Commands, sirix: Sirix, context: &mut parsers::Conte
                             resource: opts.resource,
                         }
                     } else {
-                        println!(
+                        outln!(
                                 "Cannot specify resource without database except from a database context"
                             );
                     }
                 }
             },
         },
+        Commands::Read(opts) if opts.page_size.is_some() => {
+            match paging::Page::new(&opts, opts.page_size.unwrap(), &context.context) {
+                Ok(page) => show_page(sirix, page, context),
+                Err(err) => outln!("{}", err),
+            }
+        }
+        Commands::Next => match context.page.as_ref().map(paging::Page::next) {
+            Some(Some(page)) => show_page(sirix, page, context),
+            Some(None) => outln!("this is the last page"),
+            None => outln!("no paged read - start one with read --page-size <n>"),
+        },
+        Commands::Prev => match context.page.as_ref().map(paging::Page::prev) {
+            Some(Some(page)) => show_page(sirix, page, context),
+            Some(None) => outln!("this is the first page"),
+            None => outln!("no paged read - start one with read --page-size <n>"),
+        },
+        Commands::Read(opts) if opts.streams() => {
+            context.last_error = match stream::read(&context.connection, &opts, &context.context) {
+                Ok(()) => None,
+                Err(err) => {
+                    outln!("read failed: {}", err);
+                    Some(last_status().unwrap_or(0))
+                }
+            };
+        }
         Commands::Read(opts) => {
-            let metadata = match opts.metadata {
-                Some(metadata) => match metadata.as_str() {
+            let metadata = match opts.metadata.as_deref() {
+                Some(metadata) => match metadata {
                     "all" => Some(MetadataType::All),
                     "key-and-child" => Some(MetadataType::KeyAndChild),
                     "key" => Some(MetadataType::Key),
@@ -128,38 +403,73 @@ fn execute_command(command: Commands, sirix: Sirix, context: &mut parsers::Conte
                 },
                 None => None,
             };
-            match opts.type_.unwrap().as_str() {
+            let (database, resource, db_type) = match opts.target(&context.context) {
+                Ok(target) => target,
+                Err(err) => {
+                    outln!("{}", err);
+                    return;
+                }
+            };
+            let (revision, nodekey) = (opts.revision.clone(), opts.nodekey);
+            let convert = opts.convert.clone();
+            let lookup = sirix.clone();
+            let (db, res) = (database.clone(), resource.clone());
+            match db_type.as_str() {
                 "json" => {
-                    let resource = sirix
-                        .json_database(opts.database.unwrap())
-                        .resource(opts.resource.unwrap());
-                    let response = read_json_resource(
-                        resource,
-                        opts.nodekey,
-                        opts.revision,
-                        opts.max_depth,
-                        opts.limit,
-                        opts.skip,
-                        metadata,
-                    );
-                    handle_json_response(response);
+                    let response = interruptible(move || {
+                        read_json_resource(
+                            sirix.json_database(db).resource(res),
+                            opts.nodekey,
+                            opts.revision,
+                            opts.max_depth,
+                            opts.limit,
+                            opts.skip,
+                            metadata,
+                        )
+                    });
+                    match (response, convert.as_deref()) {
+                        (Some(JsonResponse::Ok(value)), Some("xml")) => {
+                            match convert::json_to_xml(&value, &context.conversion) {
+                                Ok(xml) => handle_xml_response(XmlResponse::Ok(xml), context),
+                                Err(err) => outln!("cannot convert to XML: {}", err),
+                            }
+                        }
+                        (Some(response), _) => handle_json_response(response, context),
+                        (None, _) => {}
+                    }
                 }
                 "xml" => {
-                    let resource = sirix
-                        .xml_database(opts.database.unwrap())
-                        .resource(opts.resource.unwrap());
-                    let response = read_xml_resource(
-                        resource,
-                        opts.nodekey,
-                        opts.revision,
-                        opts.max_depth,
-                        opts.limit,
-                        opts.skip,
-                    );
-                    handle_xml_response(response);
+                    let response = interruptible(move || {
+                        read_xml_resource(
+                            sirix.xml_database(db).resource(res),
+                            opts.nodekey,
+                            opts.revision,
+                            opts.max_depth,
+                            opts.limit,
+                            opts.skip,
+                        )
+                    });
+                    match (response, convert.as_deref()) {
+                        (Some(XmlResponse::Ok(xml)), Some("json")) => {
+                            match convert::xml_to_json(&xml, &context.conversion) {
+                                Ok(value) => handle_json_response(JsonResponse::Ok(value), context),
+                                Err(err) => outln!("cannot convert to JSON: {}", err),
+                            }
+                        }
+                        (Some(response), _) => handle_xml_response(response, context),
+                        (None, _) => {}
+                    }
                 }
                 _ => panic!("Only JSON and XML supported"),
             };
+            match context.last_error {
+                None => {
+                    context.revision = revision;
+                    context.nodekey = nodekey;
+                }
+                Some(404) => help::suggest_names(lookup, &database, Some(&resource)),
+                Some(_) => {}
+            }
         }
         Commands::Delete(opts) => {
             match opts.opts {
@@ -167,119 +477,511 @@ fn execute_command(command: Commands, sirix: Sirix, context: &mut parsers::Conte
                     DeleteScopeTypes::Context(scope) => match scope.opts {
                         parsers::DeleteContextScopesImpl::Database => {
                             // TODO - need to implement context first
+                            outln!("`delete database` is not implemented yet");
+                            context.outcome.skipped = true;
                         }
                         parsers::DeleteContextScopesImpl::Resource => {
                             // TODO - need to implement context first
+                            outln!("`delete resource` is not implemented yet");
+                            context.outcome.skipped = true;
                         }
-                        parsers::DeleteContextScopesImpl::Server => match server_delete(sirix) {
-                            Ok(_) => {
-                                println!("deleted all databases");
-                            }
-                            Err(err) => {
-                                println!("failed to delete databases: {}", err);
+                        parsers::DeleteContextScopesImpl::Server => {
+                            match interruptible(move || server_delete(sirix)) {
+                                Some(Ok(_)) => {
+                                    outln!("deleted all databases");
+                                }
+                                Some(Err(err)) if !is_not_sent(&err) => {
+                                    outln!("failed to delete databases: {}", err);
+                                    context.outcome.failed = true;
+                                }
+                                Some(Err(_)) => {}
+                                None => context.outcome.failed = true,
                             }
-                        },
+                        }
                     },
                     DeleteScopeTypes::Explicit(scope) => match scope {
                         parsers::DeleteExplicitScope::Database { database } => {
-                            match database_delete(sirix.json_database(database.clone())) {
-                                Ok(_) => {
-                                    println!("database {} deleted", database);
+                            let name = database.clone();
+                            match interruptible(move || database_delete(sirix.json_database(name)))
+                            {
+                                Some(Ok(_)) => {
+                                    outln!("database {} deleted", database);
                                 }
-                                Err(err) => {
-                                    println!("failed to delete database {}: {}", database, err);
+                                Some(Err(err)) if !is_not_sent(&err) => {
+                                    outln!("failed to delete database {}: {}", database, err);
+                                    context.outcome.failed = true;
                                 }
+                                Some(Err(_)) => {}
+                                None => context.outcome.failed = true,
                             }
                         }
-                        parsers::DeleteExplicitScope::Resource { database, resource } => {
+                        parsers::DeleteExplicitScope::Resource { .. } => {
                             // TODO
+                            outln!("`delete resource` is not implemented yet");
+                            context.outcome.skipped = true;
                         }
                     },
                 },
-                DeleteOptsImpl::Node(opts) => {
+                DeleteOptsImpl::Node(_) => {
                     // TODO
+                    outln!("`delete node` is not implemented yet");
+                    context.outcome.skipped = true;
                 }
             }
         }
-        Commands::Info(opts) => match opts.database.to_owned() {
-            // TODO: Use context here
-            Some(database_name) => match opts.type_.unwrap().as_str() {
-                "xml" => {
-                    let database = sirix.xml_database(database_name);
-                    handle_xml_response(database_info_xml(database))
+        Commands::Set(opts) => match opts.name.clone() {
+            Some(name) if settings::SETTINGS.contains(&name.as_str()) => {
+                if let Err(err) = context.settings.set(&name, opts.value().as_deref()) {
+                    outln!("{}", err);
                 }
-                _ => {
-                    let database = sirix.json_database(database_name);
-                    handle_json_response(database_info_json(database))
+            }
+            Some(name) => match opts.value() {
+                Some(value) => context.variables.set(&name, &value),
+                None => match context.variables.get(&name) {
+                    Some(value) => outln!("{}", value),
+                    None => outln!("${}: undefined variable", name),
+                },
+            },
+            None => {
+                context.settings.print();
+                context.variables.print();
+            }
+        },
+        Commands::HistoryCommands(opts) => context.history.print(opts.pattern.as_deref()),
+        Commands::Alias(opts) => match opts.name {
+            None => context.aliases.print(),
+            Some(name) => {
+                let mut definition = opts.definition;
+                // accept both `alias name = body` and `alias name=body`
+                let name = match name.split_once('=') {
+                    Some((name, body)) => {
+                        if !body.is_empty() {
+                            definition.insert(0, body.to_owned());
+                        }
+                        name.to_owned()
+                    }
+                    None => {
+                        if definition.first().map(|word| word.as_str()) == Some("=") {
+                            definition.remove(0);
+                        }
+                        name
+                    }
+                };
+                if definition.is_empty() {
+                    match context.aliases.get(&name) {
+                        Some(body) => outln!("alias {} = {}", name, body),
+                        None => outln!("unknown alias {}", name),
+                    }
+                } else {
+                    context.aliases.insert(name, definition.join(" "));
+                    save_aliases(&context.aliases);
                 }
+            }
+        },
+        Commands::Unalias(opts) => {
+            if context.aliases.remove(&opts.name) {
+                save_aliases(&context.aliases);
+            } else {
+                outln!("unknown alias {}", opts.name);
+            }
+        }
+        Commands::Script(opts) => {
+            if let Err(err) = script::run_file(&opts.file, sirix, context) {
+                outln!("{}", err);
+            }
+        }
+        Commands::Eval(opts) => match script::eval(&opts.code.join(" "), sirix, context) {
+            Ok(Some(value)) => handle_json_response(JsonResponse::Ok(value), context),
+            Ok(None) => {}
+            Err(err) => outln!("{}", err),
+        },
+        Commands::Expect(opts) => {
+            if let Err(err) = expect::check(&opts.condition, context) {
+                outln!("expectation failed: {}", err);
+                context.failed_expectations += 1;
+            }
+        }
+        Commands::Record(opts) => match opts.opts {
+            parsers::RecordOptsImpl::Start { file } => match Recorder::start(&file) {
+                Ok(recorder) => context.recorder = Some(recorder),
+                Err(err) => outln!("{}", err),
             },
-            None => match opts.with_resources {
-                true => handle_json_response(server_info_with_resources(sirix)),
-                false => handle_json_response(server_info(sirix)),
+            parsers::RecordOptsImpl::Stop => match context.recorder.take() {
+                Some(recorder) => outln!("recorded to {}", recorder.path().display()),
+                None => outln!("not recording"),
             },
         },
+        Commands::Replay(opts) => match record::load_transcript(&opts.file) {
+            Ok(entries) => {
+                for entry in entries {
+                    outln!("> {}", entry.command);
+                    let args: Vec<&str> = entry.command.split_ascii_whitespace().collect();
+                    if let Err(err) = parse(&args, sirix.clone(), context) {
+                        outln!("{}", err);
+                    }
+                }
+            }
+            Err(err) => outln!("{}", err),
+        },
+        Commands::Help(opts) => help::print(opts.command.as_deref(), context),
+        Commands::Export(opts) => {
+            let server = parsers::get_server_string(context.context.clone());
+            if let Err(err) = export::export(sirix, &server, &opts) {
+                outln!("export failed: {}", err);
+            }
+        }
+        Commands::Import(opts) => {
+            let server = parsers::get_server_string(context.context.clone());
+            match import::import(sirix, &context.connection, &server, &opts) {
+                Ok(revision) => context.outcome.revision = revision,
+                Err(err) => {
+                    outln!("import failed: {}", err);
+                    context.outcome.failed = true;
+                }
+            }
+        }
+        Commands::Load(opts) => {
+            let server = parsers::get_server_string(context.context.clone());
+            if let Err(err) = load::load(sirix, &server, &opts) {
+                outln!("load failed: {}", err);
+                context.outcome.failed = true;
+            }
+        }
+        Commands::ReplayLog(opts) => match opts.database.as_ref().or(context.context.database()) {
+            Some(database) => {
+                match replay_log::replay_log(sirix, &context.connection, &database.clone(), &opts) {
+                    Ok(revision) => context.outcome.revision = revision,
+                    Err(err) => {
+                        outln!("replay-log failed: {}", err);
+                        context.outcome.failed = true;
+                    }
+                }
+            }
+            None => {
+                outln!("no database - pass --database or set a database context");
+                context.outcome.skipped = true;
+            }
+        },
+        Commands::Copy(opts) => match copy::copy(sirix, &context.connection, &opts) {
+            Ok(revision) => context.outcome.revision = revision,
+            Err(err) => {
+                outln!("copy failed: {}", err);
+                context.outcome.failed = true;
+            }
+        },
+        Commands::Migrate(opts) => {
+            if let Err(err) = migrate::migrate(&opts) {
+                outln!("migrate failed: {}", err);
+                context.outcome.failed = true;
+            }
+        }
+        Commands::Audit(opts) => match audit::query(&opts) {
+            Ok(entries) => {
+                context.variables.push_result(entries);
+                context.last_error = None;
+            }
+            Err(err) => outln!("{}", err),
+        },
+        Commands::Unlock => match context.read_only {
+            true => {
+                context.read_only = false;
+                outln!("read-only mode is off for this session");
+            }
+            false => outln!("not in read-only mode"),
+        },
+        Commands::Source(opts) => {
+            if let Err(err) = source::source_file(Path::new(&opts.file), sirix, context) {
+                outln!("{}", err);
+            }
+        }
+        Commands::Info(opts) => match opts.database.to_owned() {
+            // TODO: Use context here
+            Some(database_name) => {
+                let database = database_name.clone();
+                let lookup = sirix.clone();
+                match opts.type_.unwrap().as_str() {
+                    "xml" => {
+                        if let Some(response) = interruptible(move || {
+                            database_info_xml(sirix.xml_database(database_name))
+                        }) {
+                            handle_xml_response(response, context)
+                        }
+                    }
+                    _ => {
+                        if let Some(response) = interruptible(move || {
+                            database_info_json(sirix.json_database(database_name))
+                        }) {
+                            handle_json_response(response, context)
+                        }
+                    }
+                }
+                if context.last_error == Some(404) {
+                    help::suggest_names(lookup, &database, None);
+                }
+            }
+            None => {
+                let with_resources = opts.with_resources;
+                let response = interruptible(move || match with_resources {
+                    true => server_info_with_resources(sirix),
+                    false => server_info(sirix),
+                });
+                if let Some(response) = response {
+                    handle_json_response(response, context)
+                }
+            }
+        },
     }
 }
 
-fn parse(line: &std::vec::Vec<&str>, sirix: Sirix, context: &mut parsers::ContextStruct) {
-    let result = Commands::try_parse_from(line);
-    match result {
-        Ok(command) => execute_command(command, sirix, context),
-        Err(err) => {
-            println!("{}", err);
+/// Parse a command line split into words.
+fn parse_command(line: &[&str]) -> Result<Commands, clap::Error> {
+    match line.first() {
+        // clap answers a subcommand named `help` with its own help, even
+        // with `DisableHelpSubcommand`
+        Some(&"help") => parsers::HelpOpts::try_parse_from(line).map(Commands::Help),
+        _ => Commands::try_parse_from(line),
+    }
+}
+
+fn parse(
+    line: &std::vec::Vec<&str>,
+    sirix: Sirix,
+    context: &mut parsers::ContextStruct,
+) -> Result<(), String> {
+    // `--as-curl` is accepted on every command, so it is handled before clap
+    let as_curl = line.contains(&"--as-curl");
+    let line: Vec<&str> = line
+        .iter()
+        .copied()
+        .filter(|arg| *arg != "--as-curl")
+        .collect();
+    let command = parse_command(&line).map_err(|err| err.to_string())?;
+    if context.read_only && command.is_mutating() {
+        return Err(format!(
+            "`{}` is not allowed in read-only mode - run `unlock` to allow changes",
+            command.to_string().trim_end()
+        ));
+    }
+    let timer = match context.settings.timing && !as_curl {
+        true => Some(timing::Timer::start()),
+        false => None,
+    };
+    let recording = match command {
+        Commands::Record(_) | Commands::Replay(_) => None,
+        _ if context.recorder.is_some() => Some(command.to_string()),
+        _ => None,
+    };
+    if recording.is_some() {
+        output::start_capture();
+    }
+    let audited = match command.is_mutating() && !as_curl {
+        true => Some((command.to_string(), command.target(&context.context))),
+        false => None,
+    };
+    reset_status();
+    context.outcome = Default::default();
+    let start = Instant::now();
+    set_as_curl(as_curl);
+    execute_command(command, sirix, context);
+    set_as_curl(false);
+    context.elapsed = Some(start.elapsed());
+    if let Some((line, target)) = audited {
+        if let Err(err) = audit::record(line.trim_end().to_owned(), target, context) {
+            outln!("{}", err);
+        }
+    }
+    if let (Some(line), Some(recorder)) = (recording, context.recorder.as_mut()) {
+        if let Err(err) = recorder.record(line.trim_end().to_owned(), output::take_capture()) {
+            outln!("{}", err);
         }
+    }
+    if let Some(timer) = timer {
+        timer.report();
+    }
+    Ok(())
+}
+
+fn save_aliases(aliases: &Aliases) {
+    let aliases = serde_json::to_value(aliases.as_map()).unwrap();
+    if let Err(err) = config::save_field("aliases", aliases) {
+        outln!("{}", err);
+    }
+}
+
+/// Expand aliases, split a line into arguments and run it as a command.
+fn run_line(line: &str, sirix: Sirix, context: &mut parsers::ContextStruct) -> Result<(), String> {
+    let line = context.aliases.expand(line)?;
+    if let Some(name) = line.split_ascii_whitespace().next() {
+        help::check_command(name, &context.aliases)?;
+    }
+    let parsed = line.split_ascii_whitespace();
+    // alias definitions keep their `$1`, `$name`, ... until the alias is used
+    let interpolated = match line.split_ascii_whitespace().next() {
+        Some("alias") => parsed.map(|arg| arg.to_owned()).collect(),
+        _ => parsed
+            .map(|arg| context.variables.interpolate(arg))
+            .collect::<Result<Vec<String>, String>>()?,
     };
+    let collected: std::vec::Vec<&str> = interpolated.iter().map(|arg| arg.as_str()).collect();
+    parse(&collected, sirix, context)
 }
 
-fn repl() {
+/// Replace a `!<number>` line with the numbered entry from `history-commands`.
+fn expand_history(line: &str, history: &CommandHistory) -> Result<String, String> {
+    let number = match line.trim().strip_prefix('!') {
+        Some(number) => number,
+        None => return Ok(line.to_owned()),
+    };
+    match number.parse::<usize>().ok().and_then(|n| history.get(n)) {
+        Some(entry) => {
+            outln!("{}", entry);
+            Ok(entry.clone())
+        }
+        None => Err(format!("!{}: event not found", number)),
+    }
+}
+
+/// Connect to the server of the selected profile, prompting for settings
+/// that are neither in the profile nor given as flags. Without a password in
+/// the profile it is taken from `SIRIXSH_PASSWORD`, as in `Config::connect`.
+fn connect(
+    opts: &parsers::StartupOpts,
+    config: &Config,
+) -> Option<(String, Sirix, parsers::ContextStruct)> {
+    let (profile_name, profile) = config
+        .profile(opts.profile.as_deref())
+        .unwrap_or_else(|| ("default".to_owned(), Default::default()));
+    let agent = match build_agent(&opts.override_http(profile.http.clone())) {
+        Ok(agent) => agent,
+        Err(err) => {
+            outln!("{}", err);
+            return None;
+        }
+    };
     let mut rl = Editor::<()>::new();
-    let url = rl.readline_with_initial("url: ", ("http://localhost:9443", ""));
-    let username = rl.readline_with_initial("username: ", ("admin", ""));
-    let password = rl.readline_with_initial("password: ", ("admin", ""));
-    let url = url.unwrap();
-    let mut context = parsers::ContextStruct {
+    let url = match opts.url.clone().or(profile.url) {
+        Some(url) => url,
+        None => rl
+            .readline_with_initial("url: ", ("http://localhost:9443", ""))
+            .ok()?,
+    };
+    let username = match opts.username.clone().or(profile.username) {
+        Some(username) => username,
+        None => rl.readline_with_initial("username: ", ("admin", "")).ok()?,
+    };
+    let password = match profile
+        .password
+        .or_else(|| env::var("SIRIXSH_PASSWORD").ok())
+    {
+        Some(password) => password,
+        None => rl.readline_with_initial("password: ", ("admin", "")).ok()?,
+    };
+    let history_size = config.history_size.unwrap_or(1000);
+    let context = parsers::ContextStruct {
         context: parsers::Context::Server(url.clone()),
+        aliases: Aliases::new(config.aliases.clone()),
+        settings: Settings {
+            prompt: profile.prompt.clone().or_else(|| config.prompt.clone()),
+            ..Default::default()
+        },
+        variables: Default::default(),
+        history: CommandHistory::load(&profile_name, history_size),
+        last_error: None,
+        failed_expectations: 0,
+        recorder: None,
+        profile: profile_name.clone(),
+        username: username.clone(),
+        production: profile.production,
+        read_only: opts.read_only || profile.read_only,
+        revision: None,
+        nodekey: None,
+        elapsed: None,
+        connection: Connection::new(agent.clone(), &url, &username, &password),
+        page: None,
+        outcome: Default::default(),
+        conversion: config.conversion.clone(),
+    };
+    let sirix = create_sirix(agent, &url, &username, &password);
+    Some((profile_name, sirix, context))
+}
+
+/// Run a file of commands without startup files and exit, with a failure
+/// status if any `expect` failed.
+fn run(opts: parsers::StartupOpts, file: &str) {
+    let config = Config::load();
+    let (_, sirix, mut context) = match connect(&opts, &config) {
+        Some(connection) => connection,
+        None => process::exit(2),
+    };
+    if let Err(err) = source::source_file(Path::new(file), sirix, &mut context) {
+        outln!("{}", err);
+        process::exit(2);
+    }
+    if context.failed_expectations > 0 {
+        process::exit(1);
+    }
+}
+
+fn repl(opts: parsers::StartupOpts) {
+    let config = Config::load();
+    let (profile_name, sirix, mut context) = match connect(&opts, &config) {
+        Some(connection) => connection,
+        None => return,
     };
-    let sirix = create_sirix(&url, &username.unwrap(), &password.unwrap());
+    let mut rl = Editor::<ShellHelper>::with_config(
+        rustyline::Config::builder()
+            .max_history_size(config.history_size.unwrap_or(1000))
+            .build(),
+    );
+    let app = Commands::into_app();
+    let commands = app
+        .get_subcommands()
+        .map(|command| command.get_name().to_owned())
+        .chain(vec!["exit".to_owned(), "quit".to_owned()])
+        .collect();
+    rl.set_helper(Some(ShellHelper::new(commands, app)));
+    for entry in context.history.entries() {
+        rl.add_history_entry(entry.as_str());
+    }
+    source::source_startup_files(&profile_name, sirix.clone(), &mut context);
     loop {
-        let prompt = match context.context.clone() {
-            parsers::Context::Database {
-                server: _,
-                database,
-                db_type,
-            } => format!("{} ({}) >> ", database, format_db_type(db_type)),
-            parsers::Context::Resource {
-                server: _,
-                database,
-                db_type,
-                resource,
-            } => format!(
-                "{}/{} ({}) >> ",
-                database,
-                resource,
-                format_db_type(db_type)
-            ),
-            _ => ">> ".to_owned(),
-        };
+        if let Some(helper) = rl.helper_mut() {
+            helper.aliases = context.aliases.names();
+        }
+        let prompt = prompt::render(&context);
         let readline = rl.readline(prompt.as_str());
         match readline {
             Ok(line) => {
-                rl.add_history_entry(line.as_str());
-                let parsed = line.split_ascii_whitespace();
-                let mut collected: std::vec::Vec<&str> = parsed.collect();
-                collected.insert(0, "");
-                parse(&collected, sirix.clone(), &mut context);
+                let line = match expand_history(&line, &context.history) {
+                    Ok(line) => line,
+                    Err(err) => {
+                        outln!("{}", err);
+                        continue;
+                    }
+                };
+                if context.history.add(&line) {
+                    rl.add_history_entry(line.as_str());
+                }
+                if matches!(line.trim(), "exit" | "quit") {
+                    break;
+                }
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if let Err(err) = run_line(&line, sirix.clone(), &mut context) {
+                    outln!("{}", err);
+                }
             }
             Err(ReadlineError::Interrupted) => {
-                println!("CTRL-C");
-                break;
+                // Ctrl-C at the prompt only discards the current line
+                continue;
             }
             Err(ReadlineError::Eof) => {
-                println!("CTRL-D");
+                outln!("CTRL-D");
                 break;
             }
             Err(err) => {
-                println!("Error: {:?}", err);
+                outln!("Error: {:?}", err);
                 break;
             }
         }
@@ -287,5 +989,13 @@ fn repl() {
 }
 
 fn main() {
-    repl();
+    let opts = parsers::StartupOpts::parse();
+    interrupt::install_handler();
+    match opts.command.clone() {
+        Some(parsers::StartupCommand::Run { file }) => run(opts, &file),
+        Some(parsers::StartupCommand::Test { dir, update }) => {
+            golden::run_tests(&opts, &dir, update)
+        }
+        None => repl(opts),
+    }
 }
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The file listing what `export` wrote to a directory, read by `import`.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub database: String,
+    /// `json` or `xml`.
+    #[serde(rename = "type")]
+    pub db_type: String,
+    /// The server the database was exported from.
+    pub server: String,
+    /// When the export was made.
+    pub exported: String,
+    pub resources: Vec<ResourceManifest>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResourceManifest {
+    pub name: String,
+    /// The exported revisions, oldest first.
+    pub revisions: Vec<RevisionManifest>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevisionManifest {
+    pub revision: u64,
+    pub timestamp: Option<String>,
+    pub author: Option<String>,
+    pub commit_message: Option<String>,
+    /// The file holding the revision, relative to the manifest.
+    pub file: String,
+}
+
+impl Manifest {
+    pub fn load(dir: &Path) -> Result<Manifest, String> {
+        let path = dir.join(MANIFEST_FILE);
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+        serde_json::from_str(&contents)
+            .map_err(|err| format!("failed to parse {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(MANIFEST_FILE);
+        let contents = serde_json::to_string_pretty(self).unwrap();
+        fs::write(&path, contents)
+            .map_err(|err| format!("cannot write {}: {}", path.display(), err))
+    }
+}
@@ -0,0 +1,109 @@
+use serde_json::{Map, Value};
+
+/// The nodekey from the metadata Sirix adds to a node.
+fn node_key(node: &Value) -> Option<u64> {
+    node.get("metadata")?.get("nodeKey")?.as_u64()
+}
+
+/// Whether the items of a node read with metadata are the fields of an
+/// object rather than the items of an array.
+fn is_fields(items: &[Value]) -> bool {
+    !items.is_empty() && items.iter().all(|item| item.get("key").is_some())
+}
+
+/// Remove the metadata from a document read with metadata, leaving the
+/// plain document.
+pub fn strip(node: &Value) -> Value {
+    match (node.get("metadata"), node.get("value")) {
+        (Some(_), Some(Value::Array(items))) if is_fields(items) => Value::Object(
+            items
+                .iter()
+                .filter_map(|field| {
+                    let key = field.get("key")?.as_str()?.to_owned();
+                    Some((key, strip(field.get("value")?)))
+                })
+                .collect(),
+        ),
+        (Some(_), Some(Value::Array(items))) => Value::Array(items.iter().map(strip).collect()),
+        (Some(_), Some(value)) => strip(value),
+        _ => node.clone(),
+    }
+}
+
+/// The top level nodes of a document read with metadata, with their
+/// nodekeys: the fields of an object, each as an object of its own, or the
+/// items of an array.
+pub fn top_level(document: &Value) -> Vec<(Option<u64>, Value)> {
+    match document.get("value") {
+        Some(Value::Array(items)) if is_fields(items) => items
+            .iter()
+            .map(|field| {
+                let mut object = Map::new();
+                if let (Some(key), Some(value)) =
+                    (field.get("key").and_then(Value::as_str), field.get("value"))
+                {
+                    object.insert(key.to_owned(), strip(value));
+                }
+                (node_key(field), Value::Object(object))
+            })
+            .collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| (node_key(item), strip(item)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object() -> Value {
+        json!({
+            "metadata": {"nodeKey": 1},
+            "value": [
+                {"key": "a", "metadata": {"nodeKey": 2}, "value": {"metadata": {"nodeKey": 3}, "value": 1}},
+                {"key": "b", "metadata": {"nodeKey": 4}, "value": {
+                    "metadata": {"nodeKey": 5},
+                    "value": [{"metadata": {"nodeKey": 6}, "value": true}],
+                }},
+            ],
+        })
+    }
+
+    #[test]
+    fn strip_object() {
+        assert_eq!(strip(&object()), json!({"a": 1, "b": [true]}));
+    }
+
+    #[test]
+    fn strip_leaves_plain_documents() {
+        let plain = json!({"key": "a", "value": [1]});
+        assert_eq!(strip(&plain), plain);
+    }
+
+    #[test]
+    fn top_level_fields() {
+        assert_eq!(
+            top_level(&object()),
+            vec![(Some(2), json!({"a": 1})), (Some(4), json!({"b": [true]}))]
+        );
+    }
+
+    #[test]
+    fn top_level_items() {
+        let array = json!({
+            "metadata": {"nodeKey": 1},
+            "value": [
+                {"metadata": {"nodeKey": 2}, "value": "x"},
+                {"metadata": {"nodeKey": 3}, "value": null},
+            ],
+        });
+        assert_eq!(
+            top_level(&array),
+            vec![(Some(2), json!("x")), (Some(3), Value::Null)]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use sha2::{Digest, Sha256};
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    catalog,
+    config::{config_dir, Config},
+    export::{read_revision, resource_revisions},
+    http::Connection,
+    import::{commit, delete, original_message},
+    interrupt,
+    load::{progress, safe_name},
+    parsers::MigrateOpts,
+};
+
+/// A resource to migrate.
+#[derive(Debug, Clone)]
+struct Task {
+    database: String,
+    db_type: String,
+    resource: String,
+    /// Copied by an earlier run, so only verified.
+    done: bool,
+    /// Exists on the target although it was not copied before.
+    exists: bool,
+}
+
+impl Task {
+    fn key(&self) -> String {
+        format!("{}/{}", self.database, self.resource)
+    }
+}
+
+/// The SHA-256 hash of every revision of a resource on one of the servers,
+/// oldest first, with the revision number.
+#[derive(Debug, PartialEq)]
+struct Summary {
+    hashes: Vec<(u64, String)>,
+}
+
+impl Summary {
+    fn latest(&self) -> &str {
+        self.hashes.last().map_or("-", |(_, hash)| &hash[..12])
+    }
+
+    /// Why the copy differs from the source, if it does.
+    fn compare(&self, copy: &Summary) -> Option<String> {
+        if self.hashes.len() != copy.hashes.len() {
+            return Some(format!(
+                "{} revisions, {} copied",
+                self.hashes.len(),
+                copy.hashes.len()
+            ));
+        }
+        let differing: Vec<String> = self
+            .hashes
+            .iter()
+            .zip(&copy.hashes)
+            .filter(|((_, source), (_, copy))| source != copy)
+            .map(|((number, _), _)| number.to_string())
+            .collect();
+        match differing.is_empty() {
+            true => None,
+            false => Some(format!("revisions {} differ", differing.join(", "))),
+        }
+    }
+}
+
+/// Where the resources already copied between two profiles are remembered
+/// for `--resume`.
+fn state_path(from: &str, to: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| {
+        dir.join("migrate")
+            .join(safe_name(from))
+            .join(format!("{}.json", safe_name(to)))
+    })
+}
+
+fn load_state(from: &str, to: &str) -> BTreeSet<String> {
+    state_path(from, to)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(from: &str, to: &str, done: &BTreeSet<String>) -> Result<(), String> {
+    let path = state_path(from, to).ok_or("cannot determine the home directory")?;
+    fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| fs::write(&path, serde_json::to_string_pretty(done).unwrap()))
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
+fn summary(sirix: &Sirix, task: &Task) -> Result<Summary, String> {
+    let revisions = resource_revisions(sirix, &task.database, &task.resource, &task.db_type)?;
+    let mut hashes = Vec::new();
+    for revision in revisions {
+        let content = read_revision(
+            sirix,
+            &task.database,
+            &task.resource,
+            &task.db_type,
+            revision.number,
+        )?;
+        hashes.push((
+            revision.number,
+            format!("{:x}", Sha256::digest(content.as_bytes())),
+        ));
+    }
+    Ok(Summary { hashes })
+}
+
+/// Copy the history of a resource unless an earlier run did, and summarize
+/// it on both servers.
+fn migrate_resource(
+    source: &Sirix,
+    target: &Sirix,
+    connection: &Connection,
+    task: &Task,
+    resume: bool,
+) -> Result<(Summary, Summary), String> {
+    if !task.done {
+        match task.exists {
+            // left behind by an interrupted run
+            true if resume => delete(target, &task.database, &task.resource, &task.db_type)?,
+            true => return Err("already exists on the target".to_owned()),
+            false => {}
+        }
+        let revisions = resource_revisions(source, &task.database, &task.resource, &task.db_type)?;
+        for (i, revision) in revisions.iter().enumerate() {
+            let data = read_revision(
+                source,
+                &task.database,
+                &task.resource,
+                &task.db_type,
+                revision.number,
+            )?;
+            let message = original_message(
+                revision.commit_message.as_deref(),
+                revision.number,
+                revision.author.as_deref(),
+                revision.timestamp.as_deref(),
+            );
+            commit(
+                connection,
+                &task.database,
+                &task.resource,
+                &task.db_type,
+                data,
+                i == 0,
+                Some(message),
+            )?;
+        }
+    }
+    Ok((summary(source, task)?, summary(target, task)?))
+}
+
+/// Copy every database and resource with its history from the server of one
+/// profile to another, several resources at once, and compare the content
+/// of every revision on both sides. Ctrl-C stops after the running
+/// copies; `--resume` continues from there.
+pub fn migrate(opts: &MigrateOpts) -> Result<(), String> {
+    let config = Config::load();
+    let from = config.connect(&opts.from)?;
+    let to = config.connect(&opts.to)?;
+    to.check_writable()?;
+    let (from_url, source) = (from.url, from.sirix);
+    let (to_url, target, connection) = (to.url, to.sirix, to.connection);
+    let pattern = match &opts.databases {
+        Some(pattern) => Some(
+            glob::Pattern::new(pattern)
+                .map_err(|err| format!("invalid pattern {}: {}", pattern, err))?,
+        ),
+        None => None,
+    };
+    let existing = catalog::databases(target.clone())?;
+    let done = match opts.resume {
+        true => load_state(&opts.from, &opts.to),
+        false => BTreeSet::new(),
+    };
+    let mut tasks = Vec::new();
+    for database in catalog::databases(source.clone())? {
+        if !pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.matches(&database.name))
+        {
+            continue;
+        }
+        let on_target = existing.iter().find(|entry| entry.name == database.name);
+        if let Some(entry) = on_target.filter(|entry| entry.db_type != database.db_type) {
+            return Err(format!(
+                "database {} is of type {} on {}, but {} on {}",
+                database.name, entry.db_type, to_url, database.db_type, from_url
+            ));
+        }
+        for resource in &database.resources {
+            let task = Task {
+                database: database.name.clone(),
+                db_type: database.db_type.clone(),
+                resource: resource.clone(),
+                done: false,
+                exists: on_target.is_some_and(|entry| entry.resources.contains(resource)),
+            };
+            tasks.push(Task {
+                done: done.contains(&task.key()),
+                ..task
+            });
+        }
+    }
+    if tasks.is_empty() {
+        return Err(format!("no resources to migrate from {}", from_url));
+    }
+    outln!(
+        "migrating {} resources from {} to {}",
+        tasks.len(),
+        from_url,
+        to_url
+    );
+    let total = tasks.len();
+    let queue = Arc::new(Mutex::new(tasks.into_iter()));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = channel();
+    for _ in 0..opts.jobs.max(1) {
+        let (queue, stop, sender) = (queue.clone(), stop.clone(), sender.clone());
+        let (source, target, resume) = (source.clone(), target.clone(), opts.resume);
+        let connection = connection.clone();
+        thread::spawn(move || loop {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let task = match queue.lock().unwrap().next() {
+                Some(task) => task,
+                None => break,
+            };
+            let result = migrate_resource(&source, &target, &connection, &task, resume);
+            if sender.send((task, result)).is_err() {
+                break;
+            }
+        });
+    }
+    drop(sender);
+    interrupt::reset();
+    let mut done = done;
+    let mut results = Vec::new();
+    let mut failed = 0;
+    let mut checkpoint = Ok(());
+    progress(0, total, failed);
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(50)) {
+            Ok((task, result)) => {
+                match &result {
+                    Ok(_) if !task.done => {
+                        // checkpoint after every resource, so a crash loses
+                        // no more than the running copies
+                        done.insert(task.key());
+                        if checkpoint.is_ok() {
+                            checkpoint = save_state(&opts.from, &opts.to, &done);
+                            if checkpoint.is_err() && !stop.swap(true, Ordering::SeqCst) {
+                                eprint!("\nstopping after the running copies\n");
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(_) => failed += 1,
+                }
+                results.push((task.key(), result));
+                progress(results.len(), total, failed);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if interrupt::interrupted() && !stop.swap(true, Ordering::SeqCst) {
+                    eprint!("\nstopping after the running copies\n");
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    eprintln!();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut mismatched = 0;
+    outln!(
+        "{:<40} {:>9} {:>9}  {:<12} {:<12}  status",
+        "resource",
+        "source",
+        "target",
+        "source hash",
+        "target hash"
+    );
+    for (key, result) in &results {
+        match result {
+            Ok((source, target)) => {
+                let status = match source.compare(target) {
+                    None => "ok".to_owned(),
+                    Some(difference) => {
+                        mismatched += 1;
+                        format!("MISMATCH: {}", difference)
+                    }
+                };
+                outln!(
+                    "{:<40} {:>9} {:>9}  {:<12} {:<12}  {}",
+                    key,
+                    source.hashes.len(),
+                    target.hashes.len(),
+                    source.latest(),
+                    target.latest(),
+                    status
+                );
+            }
+            Err(err) => outln!("{:<40} failed: {}", key, err),
+        }
+    }
+    outln!(
+        "verified {} of {} resources, {} mismatched, {} failed",
+        results.len() - failed - mismatched,
+        total,
+        mismatched,
+        failed
+    );
+    if results.len() < total || failed > 0 {
+        outln!("run the same migrate with --resume to continue");
+    }
+    checkpoint?;
+    match failed + mismatched {
+        0 => Ok(()),
+        _ => Err(format!(
+            "{} of {} resources failed or mismatched",
+            failed + mismatched,
+            total
+        )),
+    }
+}
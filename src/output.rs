@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+static CAPTURE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Print a line of command output to stdout, like `println!`, also capturing
+/// it while a session is being recorded.
+macro_rules! outln {
+    () => {
+        $crate::output::write_line(String::new())
+    };
+    ($($arg:tt)*) => {
+        $crate::output::write_line(format!($($arg)*))
+    };
+}
+
+pub fn write_line(line: String) {
+    println!("{}", line);
+    if let Some(capture) = CAPTURE.lock().unwrap().as_mut() {
+        capture.push_str(&line);
+        capture.push('\n');
+    }
+}
+
+/// Write command output which is not made of whole lines, such as a
+/// streamed response. It is not captured, as it may be far larger than
+/// memory; see `capture`.
+pub fn write_raw(bytes: &[u8]) {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(bytes).and_then(|_| stdout.flush());
+}
+
+/// Capture a line standing in for output written with `write_raw`, without
+/// printing it.
+pub fn capture(line: &str) {
+    if let Some(capture) = CAPTURE.lock().unwrap().as_mut() {
+        capture.push_str(line);
+        capture.push('\n');
+    }
+}
+
+/// Start capturing the output written with `outln!`.
+pub fn start_capture() {
+    *CAPTURE.lock().unwrap() = Some(String::new());
+}
+
+/// Stop capturing, returning the output captured since `start_capture`.
+pub fn take_capture() -> String {
+    CAPTURE.lock().unwrap().take().unwrap_or_default()
+}
@@ -0,0 +1,153 @@
+use sirix_rust_client::{synchronous::sirix::Sirix, types::MetadataType};
+
+use crate::{
+    http::{read_json_resource, types::JsonResponse},
+    interrupt::interruptible,
+    metadata,
+    parsers::{Context, ReadOpts, RevisionType},
+};
+
+/// A read started with `--page-size`, remembered for `next` and `prev`.
+#[derive(Debug, Clone)]
+pub struct Page {
+    database: String,
+    resource: String,
+    nodekey: Option<u128>,
+    revision: Option<RevisionType>,
+    max_depth: Option<u64>,
+    size: u64,
+    /// The `--skip` of the pages before this one, the first page first.
+    previous: Vec<Option<u64>>,
+    /// The `--skip` of this page.
+    skip: Option<u64>,
+    /// The nodekey of the last top level node of this page, `None` on the
+    /// last page.
+    last: Option<u64>,
+}
+
+impl Page {
+    /// The first page of a read of the resource of the options or of the
+    /// resource context.
+    pub fn new(opts: &ReadOpts, size: u64, context: &Context) -> Result<Page, String> {
+        let (database, resource, db_type) = opts.target(context)?;
+        if db_type == "xml" {
+            return Err("--page-size only works with JSON resources".to_owned());
+        }
+        Ok(Page {
+            database,
+            resource,
+            nodekey: opts.nodekey,
+            revision: opts.revision.clone(),
+            max_depth: opts.max_depth,
+            size: size.max(1),
+            previous: Vec::new(),
+            skip: opts.skip,
+            last: None,
+        })
+    }
+
+    /// The page after this one, unless this is the last page.
+    pub fn next(&self) -> Option<Page> {
+        let mut page = self.clone();
+        page.previous.push(page.skip);
+        page.skip = Some(self.last?);
+        Some(page)
+    }
+
+    /// The page before this one, unless this is the first page.
+    pub fn prev(&self) -> Option<Page> {
+        let mut page = self.clone();
+        page.skip = page.previous.pop()?;
+        Some(page)
+    }
+
+    /// Make this the last page, as the page after it turned out to be empty.
+    pub fn end(&mut self) {
+        self.last = None;
+    }
+
+    /// The number of this page, counting from 1.
+    pub fn number(&self) -> usize {
+        self.previous.len() + 1
+    }
+}
+
+/// Read a page, remembering where the next page starts, and return it with
+/// the number of top level nodes on it. The nodekeys of the top level nodes
+/// are read as metadata and removed from the response.
+pub fn read(sirix: Sirix, page: &mut Page) -> Option<(JsonResponse, usize)> {
+    let resource = sirix
+        .json_database(page.database.clone())
+        .resource(page.resource.clone());
+    let (nodekey, revision, max_depth) = (page.nodekey, page.revision.clone(), page.max_depth);
+    let (size, skip) = (page.size, page.skip);
+    let response = interruptible(move || {
+        read_json_resource(
+            resource,
+            nodekey,
+            revision,
+            max_depth,
+            Some(size),
+            skip,
+            Some(MetadataType::Key),
+        )
+    })?;
+    Some(match response {
+        JsonResponse::Ok(document) => {
+            let nodes = metadata::top_level(&document);
+            page.last = match nodes.last() {
+                Some((key, _)) if nodes.len() as u64 == page.size => *key,
+                _ => None,
+            };
+            (JsonResponse::Ok(metadata::strip(&document)), nodes.len())
+        }
+        err => (err, 0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_page(last: Option<u64>) -> Page {
+        Page {
+            database: "db".to_owned(),
+            resource: "res".to_owned(),
+            nodekey: None,
+            revision: None,
+            max_depth: None,
+            size: 10,
+            previous: Vec::new(),
+            skip: None,
+            last,
+        }
+    }
+
+    #[test]
+    fn next_starts_after_the_last_node() {
+        let next = first_page(Some(42)).next().unwrap();
+        assert_eq!(next.skip, Some(42));
+        assert_eq!(next.number(), 2);
+    }
+
+    #[test]
+    fn no_next_after_the_last_page() {
+        assert!(first_page(None).next().is_none());
+        let mut page = first_page(Some(42));
+        page.end();
+        assert!(page.next().is_none());
+    }
+
+    #[test]
+    fn prev_returns_to_the_earlier_skip() {
+        let mut second = first_page(Some(42)).next().unwrap();
+        second.last = Some(99);
+        let third = second.next().unwrap();
+        assert_eq!(third.skip, Some(99));
+        let back = third.prev().unwrap();
+        assert_eq!((back.skip, back.number()), (Some(42), 2));
+        let first = back.prev().unwrap();
+        assert_eq!((first.skip, first.number()), (None, 1));
+        assert!(first.prev().is_none());
+    }
+}
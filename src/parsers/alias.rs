@@ -0,0 +1,33 @@
+use clap::{AppSettings, Clap};
+
+#[derive(Clap, Debug)]
+#[clap(setting = AppSettings::TrailingVarArg)]
+pub struct AliasOpts {
+    /// The name of the alias - lists all aliases if omitted.
+    pub name: Option<String>,
+    /// `= <command line>`, where `$1`, `$2`, ... are replaced with the
+    /// arguments the alias is called with.
+    #[clap(multiple_values = true, allow_hyphen_values = true)]
+    pub definition: Vec<String>,
+}
+
+impl std::fmt::Display for AliasOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match &self.name {
+            Some(name) => write!(f, "{} {}", name, self.definition.join(" ")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clap, Debug)]
+pub struct UnaliasOpts {
+    /// The alias to remove.
+    pub name: String,
+}
+
+impl std::fmt::Display for UnaliasOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.name)
+    }
+}
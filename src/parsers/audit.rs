@@ -0,0 +1,48 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct AuditOpts {
+    /// Only show commands changing this database.
+    #[clap(short, long)]
+    pub database: Option<String>,
+    /// Only show commands changing this resource.
+    #[clap(short, long)]
+    pub resource: Option<String>,
+    /// Only show commands run by this Sirix or OS user.
+    #[clap(short, long)]
+    pub user: Option<String>,
+    /// Only show commands run at or after this time, e.g. `2021-06-01`.
+    #[clap(short, long)]
+    pub since: Option<String>,
+    /// Only show commands the server answered with an error.
+    #[clap(short, long, takes_value = false)]
+    pub failed: bool,
+    /// Only show the last <limit> matching commands.
+    #[clap(short, long)]
+    pub limit: Option<usize>,
+}
+
+impl std::fmt::Display for AuditOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        let mut args = Vec::new();
+        if let Some(database) = &self.database {
+            args.push(format!("--database {}", database));
+        }
+        if let Some(resource) = &self.resource {
+            args.push(format!("--resource {}", resource));
+        }
+        if let Some(user) = &self.user {
+            args.push(format!("--user {}", user));
+        }
+        if let Some(since) = &self.since {
+            args.push(format!("--since {}", since));
+        }
+        if self.failed {
+            args.push("--failed".to_owned());
+        }
+        if let Some(limit) = self.limit {
+            args.push(format!("--limit {}", limit));
+        }
+        write!(f, "{}", args.join(" "))
+    }
+}
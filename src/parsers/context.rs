@@ -1,8 +1,47 @@
 use clap::Clap;
 use sirix_rust_client::types::DbType;
+use std::time::Duration;
+
+use crate::{
+    alias::Aliases, audit::Outcome, convert::ConversionRules, history::CommandHistory,
+    http::Connection, paging::Page, record::Recorder, settings::Settings, variables::Variables,
+};
+
+use super::RevisionType;
 
 pub struct ContextStruct {
     pub context: Context,
+    pub aliases: Aliases,
+    pub settings: Settings,
+    pub variables: Variables,
+    pub history: CommandHistory,
+    /// The status of the last failed request, `0` for transport errors, or
+    /// `None` if the last request succeeded.
+    pub last_error: Option<u16>,
+    /// The number of `expect` commands that failed.
+    pub failed_expectations: usize,
+    /// The transcript being recorded, if any.
+    pub recorder: Option<Recorder>,
+    /// The name of the profile connected with.
+    pub profile: String,
+    pub username: String,
+    /// Whether the profile is flagged as production.
+    pub production: bool,
+    /// Reject commands which change data, until `unlock` is run.
+    pub read_only: bool,
+    /// The revision and nodekey of the last successful read.
+    pub revision: Option<RevisionType>,
+    pub nodekey: Option<u128>,
+    /// How long the last command took.
+    pub elapsed: Option<Duration>,
+    /// For reads which are streamed rather than buffered.
+    pub connection: Connection,
+    /// The last read started with `--page-size`, for `next` and `prev`.
+    pub page: Option<Page>,
+    /// How the command being run ended, for the audit log.
+    pub outcome: Outcome,
+    /// The rules for `read --convert`, loaded with the configuration.
+    pub conversion: ConversionRules,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +60,26 @@ pub enum Context {
     },
 }
 
+impl Context {
+    /// The database of a database or resource context.
+    pub fn database(&self) -> Option<&String> {
+        match self {
+            Context::Server(_) => None,
+            Context::Database { database, .. } | Context::Resource { database, .. } => {
+                Some(database)
+            }
+        }
+    }
+
+    /// The resource of a resource context.
+    pub fn resource(&self) -> Option<&String> {
+        match self {
+            Context::Resource { resource, .. } => Some(resource),
+            _ => None,
+        }
+    }
+}
+
 pub fn get_server_string(context: Context) -> String {
     match context {
         Context::Server(server) => server,
@@ -35,6 +94,12 @@ pub struct ContextOpts {
     pub opts: ContextOptsImpl,
 }
 
+impl std::fmt::Display for ContextOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        std::fmt::Display::fmt(&self.opts, f)
+    }
+}
+
 #[derive(Clap, Debug)]
 pub enum ContextOptsImpl {
     Server,
@@ -42,6 +107,27 @@ pub enum ContextOptsImpl {
     Resource(ResourceOpts),
 }
 
+impl std::fmt::Display for ContextOptsImpl {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            ContextOptsImpl::Server => write!(f, "server"),
+            ContextOptsImpl::Database(opts) => {
+                write!(f, "database {} {}", opts.database, opts.db_type)
+            }
+            ContextOptsImpl::Resource(opts) => {
+                write!(f, "resource")?;
+                if let Some(database) = &opts.database {
+                    write!(f, " --database {}", database)?;
+                }
+                if let Some(db_type) = &opts.db_type {
+                    write!(f, " --type {}", db_type)?;
+                }
+                write!(f, " {}", opts.resource)
+            }
+        }
+    }
+}
+
 #[derive(Clap, Debug)]
 pub struct DatabaseOpts {
     pub database: String,
@@ -0,0 +1,42 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct CopyOpts {
+    /// The resource to copy, as `<database>/<resource>`.
+    pub source: String,
+    /// Where to copy it, as `<database>/<resource>` - the resource name
+    /// defaults to the source's.
+    pub destination: String,
+    /// Copy to the server of this profile instead of the current one.
+    #[clap(long)]
+    pub to: Option<String>,
+    /// Replay the whole history instead of copying the latest revision.
+    #[clap(short, long, takes_value = false)]
+    pub all_revisions: bool,
+    /// Convert the documents to this type, copying into a database of that
+    /// type.
+    #[clap(long, possible_values = &["json", "xml"])]
+    pub convert: Option<String>,
+    /// Delete the source once the copy has been verified.
+    #[clap(long = "move", takes_value = false)]
+    pub move_source: bool,
+}
+
+impl std::fmt::Display for CopyOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{} {}", self.source, self.destination)?;
+        if let Some(to) = &self.to {
+            write!(f, " --to {}", to)?;
+        }
+        if self.all_revisions {
+            write!(f, " --all-revisions")?;
+        }
+        if let Some(convert) = &self.convert {
+            write!(f, " --convert {}", convert)?;
+        }
+        if self.move_source {
+            write!(f, " --move")?;
+        }
+        Ok(())
+    }
+}
@@ -46,22 +46,17 @@ pub struct DeleteNodeOpts {
 
 impl std::fmt::Display for DeleteNodeOpts {
     fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        let database = match &self.database {
-            Some(database) => database,
-            None => "<context>",
-        };
-        let location = match &self.resource {
-            Some(resource) => database.to_owned() + "/" + resource,
-            None => database.to_owned(),
-        };
-        match &self.etag {
-            Some(etag) => write!(
-                f,
-                "resource: {}, nodekey: {}, etag: {}",
-                location, self.nodekey, etag
-            ),
-            None => write!(f, "resource: {}, nodekey: {}", location, self.nodekey),
+        write!(f, "node {}", self.nodekey)?;
+        if let Some(etag) = &self.etag {
+            write!(f, " --etag {}", etag)?;
         }
+        if let Some(database) = &self.database {
+            write!(f, " --database {}", database)?;
+        }
+        if let Some(resource) = &self.resource {
+            write!(f, " --resource {}", resource)?;
+        }
+        Ok(())
     }
 }
 
@@ -90,9 +85,9 @@ pub enum DeleteContextScopesImpl {
 impl std::fmt::Display for DeleteContextScopesImpl {
     fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
         match self {
-            DeleteContextScopesImpl::Server => write!(f, "ServerContext"),
-            DeleteContextScopesImpl::Database => write!(f, "DatabaseContext"),
-            DeleteContextScopesImpl::Resource => write!(f, "ResourceContext"),
+            DeleteContextScopesImpl::Server => write!(f, "server"),
+            DeleteContextScopesImpl::Database => write!(f, "database"),
+            DeleteContextScopesImpl::Resource => write!(f, "resource"),
         }
     }
 }
@@ -109,13 +104,12 @@ pub enum DeleteExplicitScope {
 
 impl std::fmt::Display for DeleteExplicitScope {
     fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        let string = match &self {
-            DeleteExplicitScope::Database { database } => database.to_string(),
+        match &self {
+            DeleteExplicitScope::Database { database } => write!(f, "database {}", database),
             DeleteExplicitScope::Resource { database, resource } => {
-                database.to_owned() + "/" + resource
+                write!(f, "resource {} {}", database, resource)
             }
-        };
-        write!(f, "{}", string)
+        }
     }
 }
 
@@ -132,7 +126,7 @@ impl std::fmt::Display for DeleteScopeTypes {
     fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
         match self {
             DeleteScopeTypes::Context(delete_context_scope) => {
-                std::fmt::Display::fmt(delete_context_scope, f)
+                write!(f, "context {}", delete_context_scope)
             }
             DeleteScopeTypes::Explicit(delete_explicit_scope) => {
                 std::fmt::Display::fmt(delete_explicit_scope, f)
@@ -0,0 +1,16 @@
+use clap::{AppSettings, Clap};
+
+#[derive(Clap, Debug)]
+#[clap(setting = AppSettings::TrailingVarArg)]
+pub struct ExpectOpts {
+    /// `count <op> <number>`, `path <path> <op> <json>` or `error [<status>]`,
+    /// where <op> is one of ==, !=, <, <=, >, >=.
+    #[clap(required = true, multiple_values = true, allow_hyphen_values = true)]
+    pub condition: Vec<String>,
+}
+
+impl std::fmt::Display for ExpectOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.condition.join(" "))
+    }
+}
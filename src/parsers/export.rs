@@ -0,0 +1,37 @@
+use clap::Clap;
+
+use super::RevisionType;
+
+#[derive(Clap, Debug)]
+pub struct ExportOpts {
+    /// `<database>` to export all of its resources, or `<database>/<resource>`.
+    pub target: String,
+    /// The directory to write the revisions and the manifest to.
+    pub dir: String,
+    /// Export every revision instead of only the latest one.
+    #[clap(short, long, takes_value = false)]
+    pub all_revisions: bool,
+    /// Write the documents converted to this type.
+    #[clap(long, possible_values = &["json", "xml"])]
+    pub convert: Option<String>,
+    /// The revision or range of revisions to export.
+    #[clap(subcommand)]
+    pub revision: Option<RevisionType>,
+}
+
+impl std::fmt::Display for ExportOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{} {}", self.target, self.dir)?;
+        if self.all_revisions {
+            write!(f, " --all-revisions")?;
+        }
+        if let Some(convert) = &self.convert {
+            write!(f, " --convert {}", convert)?;
+        }
+        // the revision is a subcommand, so it has to come last
+        if let Some(revision) = &self.revision {
+            write!(f, " {}", revision)?;
+        }
+        Ok(())
+    }
+}
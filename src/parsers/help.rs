@@ -0,0 +1,16 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct HelpOpts {
+    /// The command or alias to describe - lists all commands if omitted.
+    pub command: Option<String>,
+}
+
+impl std::fmt::Display for HelpOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        if let Some(command) = &self.command {
+            write!(f, "{}", command)?;
+        }
+        Ok(())
+    }
+}
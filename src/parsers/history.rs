@@ -0,0 +1,16 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct HistoryCommandsOpts {
+    /// Only list commands containing this text.
+    pub pattern: Option<String>,
+}
+
+impl std::fmt::Display for HistoryCommandsOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match &self.pattern {
+            Some(pattern) => write!(f, "{}", pattern),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct ImportOpts {
+    /// The directory written by `export`, containing a manifest.
+    pub dir: String,
+    /// The database to import into - defaults to the exported database.
+    #[clap(short, long)]
+    pub database: Option<String>,
+    /// What to do with resources which already exist: leave them alone,
+    /// delete and recreate them, or import under a new name with a suffix.
+    #[clap(long, possible_values = &["skip", "overwrite", "suffix"], default_value = "skip")]
+    pub on_conflict: String,
+}
+
+impl std::fmt::Display for ImportOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.dir)?;
+        if let Some(database) = &self.database {
+            write!(f, " --database {}", database)?;
+        }
+        write!(f, " --on-conflict {}", self.on_conflict)
+    }
+}
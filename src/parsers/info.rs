@@ -18,12 +18,19 @@ pub struct InfoOpts {
 
 impl std::fmt::Display for InfoOpts {
     fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        match self.database.to_owned() {
-            Some(database) => write!(f, "database: {}", database),
-            None => match self.with_resources {
-                true => write!(f, "server with_resources"),
-                false => write!(f, "server"),
-            },
+        let mut args = Vec::new();
+        if self.server {
+            args.push("--server".to_owned());
         }
+        if self.with_resources {
+            args.push("--with-resources".to_owned());
+        }
+        if let Some(database) = &self.database {
+            args.push(format!("--database {}", database));
+        }
+        if let Some(type_) = &self.type_ {
+            args.push(format!("--type {}", type_));
+        }
+        write!(f, "{}", args.join(" "))
     }
 }
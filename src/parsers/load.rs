@@ -0,0 +1,34 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct LoadOpts {
+    /// The files to load, e.g. `data/*.json` - each becomes a resource named
+    /// after the file.
+    pub pattern: String,
+    /// The database to create the resources in.
+    #[clap(short, long)]
+    pub database: String,
+    /// The type of the database.
+    #[clap(short, long, possible_values = &["json", "xml"], default_value = "json")]
+    pub type_: String,
+    /// How many files to upload at the same time.
+    #[clap(short, long, default_value = "4")]
+    pub jobs: usize,
+    /// Skip the files loaded by an earlier run into the same database.
+    #[clap(short, long, takes_value = false)]
+    pub resume: bool,
+}
+
+impl std::fmt::Display for LoadOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{} --database {} --type {} --jobs {}",
+            self.pattern, self.database, self.type_, self.jobs
+        )?;
+        if self.resume {
+            write!(f, " --resume")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct MigrateOpts {
+    /// The profile of the server to migrate from.
+    #[clap(long)]
+    pub from: String,
+    /// The profile of the server to migrate to.
+    #[clap(long)]
+    pub to: String,
+    /// Only migrate the databases matching this pattern, e.g. `orders-*`.
+    #[clap(short, long)]
+    pub databases: Option<String>,
+    /// How many resources to copy at the same time.
+    #[clap(short, long, default_value = "4")]
+    pub jobs: usize,
+    /// Skip the resources copied by an earlier run between the same profiles.
+    #[clap(short, long, takes_value = false)]
+    pub resume: bool,
+}
+
+impl std::fmt::Display for MigrateOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "--from {} --to {}", self.from, self.to)?;
+        if let Some(databases) = &self.databases {
+            write!(f, " --databases {}", databases)?;
+        }
+        write!(f, " --jobs {}", self.jobs)?;
+        if self.resume {
+            write!(f, " --resume")?;
+        }
+        Ok(())
+    }
+}
@@ -1,15 +1,47 @@
+pub mod alias;
+pub mod audit;
 pub mod context;
+pub mod copy;
 pub mod delete;
+pub mod expect;
+pub mod export;
+pub mod help;
+pub mod history;
+pub mod import;
 pub mod info;
+pub mod load;
+pub mod migrate;
 pub mod read;
+pub mod record;
+pub mod replay_log;
+pub mod script;
+pub mod set;
+pub mod source;
+pub mod startup;
 
+pub use alias::{AliasOpts, UnaliasOpts};
+pub use audit::AuditOpts;
 pub use context::{
     get_server_string, Context, ContextOpts, ContextOptsImpl, ContextStruct, DatabaseOpts,
     ResourceOpts,
 };
+pub use copy::CopyOpts;
 pub use delete::{
     DeleteContextScopes, DeleteContextScopesImpl, DeleteExplicitScope, DeleteNodeOpts, DeleteOpts,
     DeleteOptsImpl, DeleteScopeTypes,
 };
+pub use expect::ExpectOpts;
+pub use export::ExportOpts;
+pub use help::HelpOpts;
+pub use history::HistoryCommandsOpts;
+pub use import::ImportOpts;
 pub use info::InfoOpts;
-pub use read::ReadOpts;
+pub use load::LoadOpts;
+pub use migrate::MigrateOpts;
+pub use read::{ReadOpts, RevisionType};
+pub use record::{RecordOpts, RecordOptsImpl, ReplayOpts};
+pub use replay_log::ReplayLogOpts;
+pub use script::{EvalOpts, ScriptOpts};
+pub use set::SetOpts;
+pub use source::SourceOpts;
+pub use startup::{StartupCommand, StartupOpts};
@@ -1,4 +1,7 @@
 use clap::Clap;
+use sirix_rust_client::types::DbType;
+
+use super::Context;
 
 #[derive(Clap, Debug)]
 pub struct ReadOpts {
@@ -28,31 +31,117 @@ pub struct ReadOpts {
     /// Optionally get a metadata response instead of plain data.
     #[clap(long, short, min_values = 0, require_equals=true, possible_values = &["all", "key", "key-and-child"], default_missing_value("key-and-child"))]
     pub metadata: Option<String>,
+    /// Convert the document to the other model, see the `conversion`
+    /// settings in the configuration file.
+    #[clap(long, possible_values = &["json", "xml"], conflicts_with = "metadata")]
+    pub convert: Option<String>,
+    /// Copy the response to the output as it arrives instead of holding the
+    /// whole document in memory.
+    #[clap(long, takes_value = false, conflicts_with = "convert")]
+    pub stream: bool,
+    /// Stream the document into this file, showing the bytes written.
+    #[clap(short, long, conflicts_with = "convert")]
+    pub output: Option<String>,
+    /// Stream the top level nodes as NDJSON, reading this many at a time.
+    #[clap(long, conflicts_with_all = &["convert", "limit", "metadata"])]
+    pub chunk_size: Option<u64>,
+    /// Read this many top level nodes and remember the read, so that `next`
+    /// and `prev` show the pages after and before.
+    #[clap(long, conflicts_with_all = &["convert", "limit", "metadata", "stream", "output", "chunk-size"])]
+    pub page_size: Option<u64>,
 }
 
-// TODO handle other fields as well
 impl std::fmt::Display for ReadOpts {
     fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        write!(
-            f,
-            "nodekey: {}, maxdepth: {}, revision: {}",
-            match self.nodekey {
-                Some(nodekey) => nodekey.to_string(),
-                None => "NULL".to_owned(),
-            },
-            match self.max_depth {
-                Some(depth) => depth.to_string(),
-                None => "NULL".to_string(),
-            },
-            match &self.revision {
-                Some(revision) => revision.to_string(),
-                None => "NULL".to_owned(),
-            }
-        )
+        let mut args = Vec::new();
+        if let Some(nodekey) = self.nodekey {
+            args.push(format!("--nodekey {}", nodekey));
+        }
+        if let Some(max_depth) = self.max_depth {
+            args.push(format!("--max-depth {}", max_depth));
+        }
+        if let Some(limit) = self.limit {
+            args.push(format!("--limit {}", limit));
+        }
+        if let Some(skip) = self.skip {
+            args.push(format!("--skip {}", skip));
+        }
+        if let Some(database) = &self.database {
+            args.push(format!("--database {}", database));
+        }
+        if let Some(resource) = &self.resource {
+            args.push(format!("--resource {}", resource));
+        }
+        if let Some(type_) = &self.type_ {
+            args.push(format!("--type {}", type_));
+        }
+        if let Some(metadata) = &self.metadata {
+            args.push(format!("--metadata={}", metadata));
+        }
+        if let Some(convert) = &self.convert {
+            args.push(format!("--convert {}", convert));
+        }
+        if self.stream {
+            args.push("--stream".to_owned());
+        }
+        if let Some(output) = &self.output {
+            args.push(format!("--output {}", output));
+        }
+        if let Some(chunk_size) = self.chunk_size {
+            args.push(format!("--chunk-size {}", chunk_size));
+        }
+        if let Some(page_size) = self.page_size {
+            args.push(format!("--page-size {}", page_size));
+        }
+        // the revision is a subcommand, so it has to come last
+        if let Some(revision) = &self.revision {
+            args.push(revision.to_string());
+        }
+        write!(f, "{}", args.join(" "))
     }
 }
 
-#[derive(Clap, Debug)]
+impl ReadOpts {
+    /// Whether the read is streamed instead of buffered and printed.
+    pub fn streams(&self) -> bool {
+        self.stream || self.output.is_some() || self.chunk_size.is_some()
+    }
+
+    /// The database, resource and database type to read: those given as
+    /// options, or those of the resource context.
+    pub fn target(&self, context: &Context) -> Result<(String, String, String), String> {
+        match (&self.database, &self.resource, context) {
+            (Some(database), Some(resource), _) => Ok((
+                database.clone(),
+                resource.clone(),
+                self.type_.clone().unwrap_or_else(|| "json".to_owned()),
+            )),
+            (
+                None,
+                None,
+                Context::Resource {
+                    database,
+                    db_type,
+                    resource,
+                    ..
+                },
+            ) => Ok((
+                database.clone(),
+                resource.clone(),
+                match db_type {
+                    DbType::Json(_) => "json".to_owned(),
+                    DbType::XML(_) => "xml".to_owned(),
+                },
+            )),
+            _ => Err(
+                "no resource - pass --database, --type and --resource or set a resource context"
+                    .to_owned(),
+            ),
+        }
+    }
+}
+
+#[derive(Clap, Debug, Clone)]
 pub enum RevisionType {
     /// The timestamp of a specific revision to read.
     Timestamp {
@@ -79,23 +168,15 @@ impl std::fmt::Display for RevisionType {
                 timestamp,
                 end_timestamp,
             } => match end_timestamp {
-                Some(end_timestamp) => write!(
-                    f,
-                    "start-timestamp: {}, end-timestamp: {}",
-                    timestamp, end_timestamp
-                ),
-                None => write!(f, "timestamp: {}", timestamp),
+                Some(end_timestamp) => write!(f, "timestamp {} {}", timestamp, end_timestamp),
+                None => write!(f, "timestamp {}", timestamp),
             },
             RevisionType::Revision {
                 number: revision,
                 end_number,
             } => match end_number {
-                Some(end_number) => write!(
-                    f,
-                    "start-revision: {}, end-revision: {}",
-                    revision, end_number
-                ),
-                None => write!(f, "number: {}", revision),
+                Some(end_number) => write!(f, "revision {} {}", revision, end_number),
+                None => write!(f, "revision {}", revision),
             },
         }
     }
@@ -0,0 +1,39 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct RecordOpts {
+    #[clap(subcommand)]
+    pub opts: RecordOptsImpl,
+}
+
+impl std::fmt::Display for RecordOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match &self.opts {
+            RecordOptsImpl::Start { file } => write!(f, "start {}", file),
+            RecordOptsImpl::Stop => write!(f, "stop"),
+        }
+    }
+}
+
+#[derive(Clap, Debug)]
+pub enum RecordOptsImpl {
+    /// Start recording commands and their output.
+    Start {
+        /// The transcript to write - JSON if it ends in `.json`, else Markdown.
+        file: String,
+    },
+    /// Stop recording.
+    Stop,
+}
+
+#[derive(Clap, Debug)]
+pub struct ReplayOpts {
+    /// The JSON transcript whose commands to run.
+    pub file: String,
+}
+
+impl std::fmt::Display for ReplayOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.file)
+    }
+}
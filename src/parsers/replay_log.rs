@@ -0,0 +1,41 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct ReplayLogOpts {
+    /// The NDJSON file with one state change per line.
+    pub file: String,
+    /// The resource to commit the changes to - it is created if missing.
+    #[clap(short, long)]
+    pub resource: String,
+    /// The JSON database of the resource - defaults to the context.
+    #[clap(short, long)]
+    pub database: Option<String>,
+    /// Whether each line is the whole new document, an RFC 6902 JSON Patch
+    /// or an RFC 7396 merge patch.
+    #[clap(short, long, possible_values = &["replace", "json-patch", "merge-patch"], default_value = "replace")]
+    pub mode: String,
+    /// The field of each line holding the document or patch - defaults to
+    /// the whole line.
+    #[clap(long)]
+    pub data_field: Option<String>,
+    /// The field of each line holding the commit message.
+    #[clap(long)]
+    pub message_field: Option<String>,
+}
+
+impl std::fmt::Display for ReplayLogOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{} --resource {}", self.file, self.resource)?;
+        if let Some(database) = &self.database {
+            write!(f, " --database {}", database)?;
+        }
+        write!(f, " --mode {}", self.mode)?;
+        if let Some(data_field) = &self.data_field {
+            write!(f, " --data-field {}", data_field)?;
+        }
+        if let Some(message_field) = &self.message_field {
+            write!(f, " --message-field {}", message_field)?;
+        }
+        Ok(())
+    }
+}
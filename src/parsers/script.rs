@@ -0,0 +1,27 @@
+use clap::{AppSettings, Clap};
+
+#[derive(Clap, Debug)]
+pub struct ScriptOpts {
+    /// The Rhai script to run.
+    pub file: String,
+}
+
+impl std::fmt::Display for ScriptOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.file)
+    }
+}
+
+#[derive(Clap, Debug)]
+#[clap(setting = AppSettings::TrailingVarArg)]
+pub struct EvalOpts {
+    /// The Rhai code to evaluate.
+    #[clap(required = true, multiple_values = true, allow_hyphen_values = true)]
+    pub code: Vec<String>,
+}
+
+impl std::fmt::Display for EvalOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.code.join(" "))
+    }
+}
@@ -0,0 +1,32 @@
+use clap::{AppSettings, Clap};
+
+#[derive(Clap, Debug)]
+#[clap(setting = AppSettings::TrailingVarArg)]
+pub struct SetOpts {
+    /// The setting or variable to change - lists all of them if omitted.
+    pub name: Option<String>,
+    /// The new value, e.g. `on` or `off` - switches are toggled if omitted,
+    /// variables are printed.
+    #[clap(requires("name"), multiple_values = true, allow_hyphen_values = true)]
+    pub value: Vec<String>,
+}
+
+impl SetOpts {
+    /// The words of the value joined into one, if there are any.
+    pub fn value(&self) -> Option<String> {
+        match self.value.is_empty() {
+            true => None,
+            false => Some(self.value.join(" ")),
+        }
+    }
+}
+
+impl std::fmt::Display for SetOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        match (&self.name, &self.value()) {
+            (Some(name), Some(value)) => write!(f, "{} {}", name, value),
+            (Some(name), None) => write!(f, "{}", name),
+            _ => Ok(()),
+        }
+    }
+}
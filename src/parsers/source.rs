@@ -0,0 +1,13 @@
+use clap::Clap;
+
+#[derive(Clap, Debug)]
+pub struct SourceOpts {
+    /// The file of shell commands to run, one per line.
+    pub file: String,
+}
+
+impl std::fmt::Display for SourceOpts {
+    fn fmt(self: &Self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{}", self.file)
+    }
+}
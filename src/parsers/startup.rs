@@ -0,0 +1,126 @@
+use clap::Clap;
+
+use crate::http::HttpOptions;
+
+/// The command line flags the shell is started with.
+#[derive(Clap, Debug)]
+#[clap(version = "0.1", author = "Moshe Uminer")]
+pub struct StartupOpts {
+    /// The connection profile from ~/.sirixsh/config.json to use.
+    #[clap(short, long)]
+    pub profile: Option<String>,
+    /// The URL of the Sirix server - overrides the profile.
+    #[clap(long)]
+    pub url: Option<String>,
+    /// The user to log in as - overrides the profile.
+    #[clap(short, long)]
+    pub username: Option<String>,
+    /// Timeout in seconds for establishing a connection.
+    #[clap(long)]
+    pub connect_timeout: Option<u64>,
+    /// Timeout in seconds for reading a response.
+    #[clap(long)]
+    pub read_timeout: Option<u64>,
+    /// How often to retry reads and info requests after a transport error.
+    #[clap(long)]
+    pub retries: Option<u32>,
+    /// HTTP(S) proxy to send requests through.
+    #[clap(long)]
+    pub proxy: Option<String>,
+    /// PEM file with additional trusted CA certificates.
+    #[clap(long)]
+    pub ca_bundle: Option<String>,
+    /// PEM file with a client certificate chain. Requires --client-key.
+    #[clap(long, requires("client-key"))]
+    pub client_cert: Option<String>,
+    /// PEM file with the private key for --client-cert.
+    #[clap(long, requires("client-cert"))]
+    pub client_key: Option<String>,
+    /// Do not verify the server certificate.
+    #[clap(long, takes_value = false)]
+    pub insecure: bool,
+    /// Reject every command which changes data until `unlock` is run.
+    #[clap(long, takes_value = false)]
+    pub read_only: bool,
+    #[clap(subcommand)]
+    pub command: Option<StartupCommand>,
+}
+
+#[derive(Clap, Debug, Clone)]
+pub enum StartupCommand {
+    /// Run a file of shell commands and exit. Startup files are not run, and
+    /// the exit status is 1 if an `expect` failed.
+    Run {
+        /// The file of shell commands to run.
+        file: String,
+    },
+    /// Run every `*.sirixsh` file in a directory and compare its output with
+    /// the `.golden` file next to it.
+    Test {
+        /// The directory containing the tests.
+        dir: String,
+        /// Write the output of every test to its golden file instead of
+        /// comparing them.
+        #[clap(long, takes_value = false)]
+        update: bool,
+    },
+}
+
+impl StartupOpts {
+    /// Apply the flags given on the command line on top of a profile's settings.
+    pub fn override_http(&self, mut options: HttpOptions) -> HttpOptions {
+        if self.connect_timeout.is_some() {
+            options.connect_timeout = self.connect_timeout;
+        }
+        if self.read_timeout.is_some() {
+            options.read_timeout = self.read_timeout;
+        }
+        if self.retries.is_some() {
+            options.retries = self.retries;
+        }
+        if self.proxy.is_some() {
+            options.proxy = self.proxy.clone();
+        }
+        if self.ca_bundle.is_some() {
+            options.ca_bundle = self.ca_bundle.clone();
+        }
+        if self.client_cert.is_some() {
+            options.client_cert = self.client_cert.clone();
+            options.client_key = self.client_key.clone();
+        }
+        options.insecure |= self.insecure;
+        options
+    }
+
+    /// The flags as they would be given on the command line, without the
+    /// command, to start another shell connected the same way.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut flag = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                args.push(format!("--{}", name));
+                args.push(value);
+            }
+        };
+        flag("profile", self.profile.clone());
+        flag("url", self.url.clone());
+        flag("username", self.username.clone());
+        flag(
+            "connect-timeout",
+            self.connect_timeout.map(|t| t.to_string()),
+        );
+        flag("read-timeout", self.read_timeout.map(|t| t.to_string()));
+        flag("retries", self.retries.map(|r| r.to_string()));
+        flag("proxy", self.proxy.clone());
+        flag("ca-bundle", self.ca_bundle.clone());
+        flag("client-cert", self.client_cert.clone());
+        flag("client-key", self.client_key.clone());
+        if self.insecure {
+            args.push("--insecure".to_owned());
+        }
+        if self.read_only {
+            args.push("--read-only".to_owned());
+        }
+        args
+    }
+}
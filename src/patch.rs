@@ -0,0 +1,187 @@
+use serde_json::Value;
+
+/// Split a JSON pointer into the pointer to the parent and the unescaped
+/// last reference token.
+fn split_pointer(path: &str) -> Result<(&str, String), String> {
+    match path.rfind('/') {
+        Some(index) => Ok((
+            &path[..index],
+            path[index + 1..].replace("~1", "/").replace("~0", "~"),
+        )),
+        None => Err(format!("invalid JSON pointer `{}`", path)),
+    }
+}
+
+fn array_index(token: &str, len: usize) -> Result<usize, String> {
+    match token.parse::<usize>() {
+        Ok(index) if index <= len => Ok(index),
+        _ => Err(format!("invalid array index `{}`", token)),
+    }
+}
+
+fn parent<'a>(doc: &'a mut Value, pointer: &str) -> Result<&'a mut Value, String> {
+    doc.pointer_mut(pointer)
+        .ok_or_else(|| format!("`{}` does not exist", pointer))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (pointer, token) = split_pointer(path)?;
+    match parent(doc, pointer)? {
+        Value::Object(object) => {
+            object.insert(token, value);
+        }
+        Value::Array(array) if token == "-" => array.push(value),
+        Value::Array(array) => {
+            let index = array_index(&token, array.len())?;
+            array.insert(index, value);
+        }
+        _ => return Err(format!("cannot add to `{}`", pointer)),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, String> {
+    let (pointer, token) = split_pointer(path)?;
+    let removed = match parent(doc, pointer)? {
+        Value::Object(object) => object.remove(&token),
+        Value::Array(array) => match array_index(&token, array.len())? {
+            index if index < array.len() => Some(array.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    };
+    removed.ok_or_else(|| format!("`{}` does not exist", path))
+}
+
+fn field<'a>(operation: &'a Value, name: &str) -> Result<&'a Value, String> {
+    operation
+        .get(name)
+        .ok_or_else(|| format!("patch operation without `{}`", name))
+}
+
+fn string_field<'a>(operation: &'a Value, name: &str) -> Result<&'a str, String> {
+    field(operation, name)?
+        .as_str()
+        .ok_or_else(|| format!("`{}` of a patch operation is not a string", name))
+}
+
+/// Apply an RFC 6902 JSON Patch, an array of operations, to `doc`. If an
+/// operation fails, `doc` is left as it was.
+pub fn apply_json_patch(doc: &mut Value, patch: &Value) -> Result<(), String> {
+    let operations = patch.as_array().ok_or("a JSON Patch must be an array")?;
+    let mut patched = doc.clone();
+    for operation in operations {
+        apply_operation(&mut patched, operation)?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, operation: &Value) -> Result<(), String> {
+    let path = string_field(operation, "path")?;
+    match string_field(operation, "op")? {
+        "add" => add(doc, path, field(operation, "value")?.clone())?,
+        "remove" => {
+            remove(doc, path)?;
+        }
+        "replace" => {
+            let value = field(operation, "value")?.clone();
+            *parent(doc, path)? = value;
+        }
+        "move" => {
+            let value = remove(doc, string_field(operation, "from")?)?;
+            add(doc, path, value)?;
+        }
+        "copy" => {
+            let from = string_field(operation, "from")?;
+            let value = doc
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| format!("`{}` does not exist", from))?;
+            add(doc, path, value)?;
+        }
+        "test" => {
+            if doc.pointer(path) != Some(field(operation, "value")?) {
+                return Err(format!("test of `{}` failed", path));
+            }
+        }
+        op => return Err(format!("unknown patch operation `{}`", op)),
+    }
+    Ok(())
+}
+
+/// Apply an RFC 7396 JSON Merge Patch to `doc`.
+pub fn apply_merge_patch(doc: &mut Value, patch: &Value) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        _ => {
+            *doc = patch.clone();
+            return;
+        }
+    };
+    if !doc.is_object() {
+        *doc = Value::Object(Default::default());
+    }
+    let object = doc.as_object_mut().unwrap();
+    for (key, value) in patch {
+        match value {
+            Value::Null => {
+                object.remove(key);
+            }
+            _ => apply_merge_patch(object.entry(key.clone()).or_insert(Value::Null), value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_patch_appends_with_dash() {
+        let mut doc = json!({"items": [1, 2]});
+        apply_json_patch(
+            &mut doc,
+            &json!([{"op": "add", "path": "/items/-", "value": 3}]),
+        )
+        .unwrap();
+        assert_eq!(doc, json!({"items": [1, 2, 3]}));
+    }
+
+    #[test]
+    fn json_patch_unescapes_tokens() {
+        let mut doc = json!({"a/b": 1, "c~d": 2});
+        let patch = json!([
+            {"op": "replace", "path": "/a~1b", "value": 10},
+            {"op": "remove", "path": "/c~0d"},
+        ]);
+        apply_json_patch(&mut doc, &patch).unwrap();
+        assert_eq!(doc, json!({"a/b": 10}));
+    }
+
+    #[test]
+    fn json_patch_failing_test_leaves_document() {
+        let mut doc = json!({"status": "open"});
+        let patch = json!([
+            {"op": "add", "path": "/owner", "value": "alice"},
+            {"op": "test", "path": "/status", "value": "closed"},
+        ]);
+        assert_eq!(
+            apply_json_patch(&mut doc, &patch),
+            Err("test of `/status` failed".to_owned())
+        );
+        assert_eq!(doc, json!({"status": "open"}));
+    }
+
+    #[test]
+    fn merge_patch_removes_nulls_and_merges_objects() {
+        let mut doc = json!({"a": 1, "b": {"c": 2, "d": 3}});
+        apply_merge_patch(&mut doc, &json!({"a": null, "b": {"c": 4}, "e": [5]}));
+        assert_eq!(doc, json!({"b": {"c": 4, "d": 3}, "e": [5]}));
+    }
+}
@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use crate::{
+    http::format_db_type,
+    parsers::{Context, ContextStruct, RevisionType},
+};
+
+/// Shown in front of the prompt when connected to a production profile.
+const PRODUCTION_MARKER: &str = "\x1b[1;41;97m PROD \x1b[0m ";
+/// Shown in front of the prompt in read-only mode.
+const READ_ONLY_MARKER: &str = "\x1b[1;33m[read-only]\x1b[0m ";
+
+/// The colors usable as `{red}`, ... in a prompt template.
+const COLORS: &[(&str, &str)] = &[
+    ("reset", "\x1b[0m"),
+    ("bold", "\x1b[1m"),
+    ("red", "\x1b[31m"),
+    ("green", "\x1b[32m"),
+    ("yellow", "\x1b[33m"),
+    ("blue", "\x1b[34m"),
+    ("magenta", "\x1b[35m"),
+    ("cyan", "\x1b[36m"),
+    ("grey", "\x1b[90m"),
+];
+
+/// The host of a server URL, without scheme, credentials or path.
+fn host(url: &str) -> &str {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split('/').next().unwrap_or(url);
+    url.rsplit('@').next().unwrap_or(url)
+}
+
+fn revision(revision: &RevisionType) -> String {
+    match revision {
+        RevisionType::Revision {
+            number,
+            end_number: None,
+        } => number.to_string(),
+        RevisionType::Revision {
+            number,
+            end_number: Some(end_number),
+        } => format!("{}-{}", number, end_number),
+        RevisionType::Timestamp {
+            timestamp,
+            end_timestamp: None,
+        } => timestamp.clone(),
+        RevisionType::Timestamp {
+            timestamp,
+            end_timestamp: Some(end_timestamp),
+        } => format!("{}-{}", timestamp, end_timestamp),
+    }
+}
+
+fn elapsed(elapsed: Duration) -> String {
+    match elapsed.as_millis() {
+        millis if millis < 1000 => format!("{}ms", millis),
+        _ => format!("{:.1}s", elapsed.as_secs_f64()),
+    }
+}
+
+/// The value of a placeholder, or `None` if `name` is not a placeholder.
+fn placeholder(name: &str, context: &ContextStruct) -> Option<String> {
+    let (database, db_type, resource) = match &context.context {
+        Context::Server(_) => (None, None, None),
+        Context::Database {
+            database, db_type, ..
+        } => (Some(database), Some(db_type), None),
+        Context::Resource {
+            database,
+            db_type,
+            resource,
+            ..
+        } => (Some(database), Some(db_type), Some(resource)),
+    };
+    let value = match name {
+        "profile" => context.profile.clone(),
+        "user" => context.username.clone(),
+        "host" => match &context.context {
+            Context::Server(server)
+            | Context::Database { server, .. }
+            | Context::Resource { server, .. } => host(server).to_owned(),
+        },
+        "db" => database.cloned().unwrap_or_default(),
+        "res" => resource.cloned().unwrap_or_default(),
+        "type" => db_type
+            .map(|db_type| format_db_type(db_type.clone()))
+            .unwrap_or_default(),
+        "revision" => context.revision.as_ref().map(revision).unwrap_or_default(),
+        "nodekey" => context
+            .nodekey
+            .map(|nodekey| nodekey.to_string())
+            .unwrap_or_default(),
+        "status" => match context.last_error {
+            None => "ok".to_owned(),
+            Some(0) => "error".to_owned(),
+            Some(status) => status.to_string(),
+        },
+        "elapsed" => context.elapsed.map(elapsed).unwrap_or_default(),
+        _ => {
+            return COLORS
+                .iter()
+                .find(|(color, _)| *color == name)
+                .map(|(_, code)| (*code).to_owned())
+        }
+    };
+    Some(value)
+}
+
+/// Fill in the placeholders of a prompt template, e.g.
+/// `{green}{user}@{host}{reset} {db}/{res} [{status}] >> `. Unknown
+/// placeholders are left as they are.
+fn render_template(template: &str, context: &ContextStruct) -> String {
+    let mut prompt = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        prompt.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let value = rest
+            .find('}')
+            .and_then(|close| Some((close, placeholder(&rest[1..close], context)?)));
+        match value {
+            Some((close, value)) => {
+                prompt.push_str(&value);
+                rest = &rest[close + 1..];
+            }
+            None => {
+                prompt.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    prompt.push_str(rest);
+    if prompt.contains('\x1b') {
+        // don't let a color spill into the input line
+        prompt.push_str("\x1b[0m");
+    }
+    if !template.ends_with(char::is_whitespace) {
+        prompt.push(' ');
+    }
+    prompt
+}
+
+fn default_prompt(context: &Context) -> String {
+    match context.clone() {
+        Context::Database {
+            server: _,
+            database,
+            db_type,
+        } => format!("{} ({}) >> ", database, format_db_type(db_type)),
+        Context::Resource {
+            server: _,
+            database,
+            db_type,
+            resource,
+        } => format!(
+            "{}/{} ({}) >> ",
+            database,
+            resource,
+            format_db_type(db_type)
+        ),
+        _ => ">> ".to_owned(),
+    }
+}
+
+/// The prompt for the current context, from the `prompt` setting if one is
+/// set.
+pub fn render(context: &ContextStruct) -> String {
+    let prompt = match &context.settings.prompt {
+        Some(template) => render_template(template, context),
+        None => default_prompt(&context.context),
+    };
+    let mut marked = String::new();
+    if context.production {
+        marked.push_str(PRODUCTION_MARKER);
+    }
+    if context.read_only {
+        marked.push_str(READ_ONLY_MARKER);
+    }
+    marked.push_str(&prompt);
+    marked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alias::Aliases, history::CommandHistory, http::Connection, settings::Settings};
+    use sirix_rust_client::types::{DbType, Json};
+
+    fn context() -> ContextStruct {
+        ContextStruct {
+            context: Context::Resource {
+                server: "https://admin@sirix.example.com:9443/".to_owned(),
+                database: "orders".to_owned(),
+                db_type: DbType::Json(Json),
+                resource: "current".to_owned(),
+            },
+            aliases: Aliases::default(),
+            settings: Settings::default(),
+            variables: Default::default(),
+            history: CommandHistory::load("prompt-test", 0),
+            last_error: None,
+            failed_expectations: 0,
+            recorder: None,
+            profile: "local".to_owned(),
+            username: "admin".to_owned(),
+            production: false,
+            read_only: false,
+            revision: Some(RevisionType::Revision {
+                number: 3,
+                end_number: None,
+            }),
+            nodekey: Some(7),
+            elapsed: Some(Duration::from_millis(42)),
+            connection: Connection::new(ureq::Agent::new(), "https://localhost", "", ""),
+            page: None,
+            outcome: Default::default(),
+            conversion: Default::default(),
+        }
+    }
+
+    #[test]
+    fn host_without_scheme_credentials_or_path() {
+        assert_eq!(
+            host("https://admin@sirix.example.com:9443/"),
+            "sirix.example.com:9443"
+        );
+        assert_eq!(host("localhost:9443"), "localhost:9443");
+    }
+
+    #[test]
+    fn placeholders() {
+        let context = context();
+        let rendered = render_template(
+            "{profile} {user} {host} {db} {res} {type} {revision} {nodekey} {status} {elapsed}",
+            &context,
+        );
+        assert_eq!(
+            rendered,
+            "local admin sirix.example.com:9443 orders current JSON 3 7 ok 42ms "
+        );
+    }
+
+    #[test]
+    fn status_and_elapsed() {
+        let mut context = context();
+        context.last_error = Some(404);
+        context.elapsed = Some(Duration::from_millis(1500));
+        assert_eq!(
+            render_template("{status} {elapsed}>", &context),
+            "404 1.5s> "
+        );
+        context.last_error = Some(0);
+        assert_eq!(render_template("{status} ", &context), "error ");
+    }
+
+    #[test]
+    fn placeholders_outside_a_context() {
+        let mut context = context();
+        context.context = Context::Server("http://localhost:9443".to_owned());
+        context.revision = None;
+        context.nodekey = None;
+        assert_eq!(
+            render_template("{host}|{db}|{res}|{type}|{revision}|{nodekey}", &context),
+            "localhost:9443||||| "
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_kept() {
+        let context = context();
+        assert_eq!(
+            render_template("{nope} {db} {", &context),
+            "{nope} orders { "
+        );
+    }
+
+    #[test]
+    fn colors_are_reset() {
+        let context = context();
+        assert_eq!(
+            render_template("{green}{db}{reset} >> ", &context),
+            "\x1b[32morders\x1b[0m >> \x1b[0m"
+        );
+    }
+
+    #[test]
+    fn markers() {
+        let mut context = context();
+        assert_eq!(render(&context), "orders/current (JSON) >> ");
+        context.production = true;
+        context.read_only = true;
+        assert_eq!(
+            render(&context),
+            format!(
+                "{}{}orders/current (JSON) >> ",
+                PRODUCTION_MARKER, READ_ONLY_MARKER
+            )
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A command and its output in a session transcript.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Entry {
+    pub timestamp: String,
+    pub command: String,
+    pub output: String,
+}
+
+enum Format {
+    Markdown,
+    Json,
+}
+
+/// Records commands with their output into a transcript, started with
+/// `record start <file>`. Files ending in `.json` are written as a JSON
+/// array which can be replayed; all others as Markdown.
+pub struct Recorder {
+    path: PathBuf,
+    format: Format,
+    entries: Vec<Entry>,
+}
+
+impl Recorder {
+    pub fn start(path: &str) -> Result<Recorder, String> {
+        let path = PathBuf::from(path);
+        let format = match path.extension() {
+            Some(extension) if extension == "json" => Format::Json,
+            _ => Format::Markdown,
+        };
+        let recorder = Recorder {
+            path,
+            format,
+            entries: Vec::new(),
+        };
+        let header = match recorder.format {
+            Format::Markdown => format!("# sirixsh session {}\n", Local::now().to_rfc3339()),
+            Format::Json => "[]\n".to_owned(),
+        };
+        fs::write(&recorder.path, header).map_err(|err| recorder.write_error(err))?;
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write_error(&self, err: std::io::Error) -> String {
+        format!("cannot write {}: {}", self.path.display(), err)
+    }
+
+    pub fn record(&mut self, command: String, output: String) -> Result<(), String> {
+        let entry = Entry {
+            timestamp: Local::now().to_rfc3339(),
+            command,
+            output,
+        };
+        match self.format {
+            Format::Markdown => {
+                let mut file = OpenOptions::new()
+                    .append(true)
+                    .open(&self.path)
+                    .map_err(|err| self.write_error(err))?;
+                write!(
+                    file,
+                    "\n## `{}`\n\n_{}_\n\n```\n{}```\n",
+                    entry.command, entry.timestamp, entry.output
+                )
+                .map_err(|err| self.write_error(err))?;
+            }
+            Format::Json => {
+                self.entries.push(entry);
+                let contents = serde_json::to_string_pretty(&self.entries).unwrap();
+                fs::write(&self.path, contents).map_err(|err| self.write_error(err))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the entries of a JSON transcript for `replay`.
+pub fn load_transcript(path: &str) -> Result<Vec<Entry>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path, err))?;
+    serde_json::from_str(&contents)
+        .map_err(|err| format!("{} is not a JSON transcript: {}", path, err))
+}
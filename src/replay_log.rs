@@ -0,0 +1,132 @@
+use serde::Serialize;
+use serde_json::Value;
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::fs;
+
+use crate::{
+    catalog,
+    export::resource_revisions,
+    http::{format_error, read_json_resource, types::JsonResponse, Connection},
+    import::commit,
+    interrupt::{self, interruptible},
+    parsers::ReplayLogOpts,
+    patch::{apply_json_patch, apply_merge_patch},
+};
+
+/// A commit made from a line of the log, written to `<file>.commits.json`.
+#[derive(Serialize, Debug)]
+struct CommitReport {
+    line: usize,
+    revision: Option<u64>,
+    commit_message: Option<String>,
+}
+
+fn json_response(response: Option<JsonResponse>) -> Result<Value, String> {
+    match response {
+        Some(JsonResponse::Ok(value)) => Ok(value),
+        Some(JsonResponse::Err(err)) => Err(format_error(err)),
+        None => Err("request cancelled".to_owned()),
+    }
+}
+
+/// Commit every line of an NDJSON log as a new revision of a resource,
+/// building its history from the log.
+pub fn replay_log(
+    sirix: Sirix,
+    connection: &Connection,
+    database: &str,
+    opts: &ReplayLogOpts,
+) -> Result<Option<u64>, String> {
+    let contents = fs::read_to_string(&opts.file)
+        .map_err(|err| format!("cannot read {}: {}", opts.file, err))?;
+    let entry = catalog::databases(sirix.clone())?
+        .into_iter()
+        .find(|entry| entry.name == database);
+    let exists = match entry {
+        Some(entry) if entry.db_type != "json" => {
+            return Err(format!("database {} is not a JSON database", database))
+        }
+        Some(entry) => entry.resources.contains(&opts.resource),
+        None => false,
+    };
+    let mut doc = match (exists, opts.mode.as_str()) {
+        (true, "json-patch") | (true, "merge-patch") => {
+            let resource = sirix
+                .json_database(database.to_owned())
+                .resource(opts.resource.clone());
+            json_response(interruptible(move || {
+                read_json_resource(resource, None, None, None, None, None, None)
+            }))?
+        }
+        _ => Value::Object(Default::default()),
+    };
+    let mut created = exists;
+    let mut commits = Vec::new();
+    interrupt::reset();
+    for (number, line) in contents.lines().enumerate() {
+        let number = number + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if interrupt::interrupted() {
+            outln!("stopped before line {}", number);
+            break;
+        }
+        let change: Value = serde_json::from_str(line)
+            .map_err(|err| format!("{}:{}: {}", opts.file, number, err))?;
+        let data = match &opts.data_field {
+            Some(field) => change
+                .get(field)
+                .ok_or_else(|| format!("{}:{}: no field {}", opts.file, number, field))?,
+            None => &change,
+        };
+        match opts.mode.as_str() {
+            "json-patch" => apply_json_patch(&mut doc, data)
+                .map_err(|err| format!("{}:{}: {}", opts.file, number, err))?,
+            "merge-patch" => apply_merge_patch(&mut doc, data),
+            _ => doc = data.clone(),
+        }
+        let commit_message = opts.message_field.as_ref().and_then(|field| {
+            change.get(field).map(|message| match message {
+                Value::String(message) => message.clone(),
+                message => message.to_string(),
+            })
+        });
+        commit(
+            connection,
+            database,
+            &opts.resource,
+            "json",
+            doc.to_string(),
+            !created,
+            commit_message.clone(),
+        )
+        .map_err(|err| format!("{}:{}: {}", opts.file, number, err))?;
+        created = true;
+        commits.push(CommitReport {
+            line: number,
+            revision: None,
+            commit_message,
+        });
+    }
+    outln!(
+        "committed {} revisions to {}/{}",
+        commits.len(),
+        database,
+        opts.resource
+    );
+    if commits.is_empty() {
+        return Ok(None);
+    }
+    // the commits are the newest revisions of the resource
+    let revisions = resource_revisions(&sirix, database, &opts.resource, "json")?;
+    let first = revisions.len().saturating_sub(commits.len());
+    for (commit, revision) in commits.iter_mut().zip(&revisions[first..]) {
+        commit.revision = Some(revision.number);
+    }
+    let path = format!("{}.commits.json", opts.file);
+    fs::write(&path, serde_json::to_string_pretty(&commits).unwrap())
+        .map_err(|err| format!("cannot write {}: {}", path, err))?;
+    outln!("wrote {}", path);
+    Ok(commits.last().and_then(|commit| commit.revision))
+}
@@ -0,0 +1,363 @@
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope};
+use serde_json::Value;
+use sirix_rust_client::{synchronous::sirix::Sirix, types::Insert};
+use std::path::PathBuf;
+
+use crate::{
+    audit::{self, Session, Target},
+    http::{
+        create_json_resource, create_xml_resource, diff_json_resource, format_error, query,
+        query_json_resource, read_json_resource, read_xml_resource, resource_history,
+        trace::reset_status,
+        types::{JsonResponse, XmlResponse},
+        update_json_resource, update_xml_resource,
+    },
+    interrupt::interruptible,
+    parsers::{read::RevisionType, ContextStruct},
+};
+
+type ScriptResult = Result<Dynamic, Box<EvalAltResult>>;
+
+/// The `sirix` object available to scripts. Every method blocks until the
+/// server has answered and returns the response as a script value.
+#[derive(Clone)]
+struct SirixApi {
+    sirix: Sirix,
+    /// Reject the methods changing a resource, see `--read-only`.
+    read_only: bool,
+    /// Who runs the script where, for the audit log.
+    session: Session,
+    /// The command running the script, e.g. `script load.rhai`.
+    origin: String,
+}
+
+fn json_result(response: Option<JsonResponse>) -> ScriptResult {
+    match response {
+        Some(JsonResponse::Ok(value)) => rhai::serde::to_dynamic(value),
+        Some(JsonResponse::Err(err)) => Err(format_error(err).into()),
+        None => Err("request cancelled".into()),
+    }
+}
+
+fn xml_result(response: Option<XmlResponse>) -> ScriptResult {
+    match response {
+        Some(XmlResponse::Ok(xml)) => Ok(xml.into()),
+        Some(XmlResponse::Err(err)) => Err(format_error(err).into()),
+        None => Err("request cancelled".into()),
+    }
+}
+
+fn int_option(options: &Map, key: &str) -> Result<Option<i64>, Box<EvalAltResult>> {
+    match options.get(key) {
+        Some(value) => value
+            .as_int()
+            .map(Some)
+            .map_err(|_| format!("option {} must be an integer", key).into()),
+        None => Ok(None),
+    }
+}
+
+/// The arguments of a read, taken from the options map of `read`.
+struct ReadOptions {
+    nodekey: Option<u128>,
+    revision: Option<RevisionType>,
+    max_depth: Option<u64>,
+    limit: Option<u64>,
+    skip: Option<u64>,
+}
+
+fn read_options(options: &Map) -> Result<ReadOptions, Box<EvalAltResult>> {
+    let revision = match (int_option(options, "revision")?, options.get("timestamp")) {
+        (Some(number), _) => Some(RevisionType::Revision {
+            number: number as u64,
+            end_number: int_option(options, "endRevision")?.map(|end| end as u64),
+        }),
+        (None, Some(timestamp)) => Some(RevisionType::Timestamp {
+            timestamp: timestamp.to_string(),
+            end_timestamp: options.get("endTimestamp").map(|end| end.to_string()),
+        }),
+        (None, None) => None,
+    };
+    Ok(ReadOptions {
+        nodekey: int_option(options, "nodeKey")?.map(|key| key as u128),
+        revision,
+        max_depth: int_option(options, "maxDepth")?.map(|depth| depth as u64),
+        limit: int_option(options, "limit")?.map(|limit| limit as u64),
+        skip: int_option(options, "skip")?.map(|skip| skip as u64),
+    })
+}
+
+fn insert_position(position: &str) -> Result<Insert, Box<EvalAltResult>> {
+    match position {
+        "child" => Ok(Insert::Child),
+        "left" => Ok(Insert::Left),
+        "right" => Ok(Insert::Right),
+        "replace" => Ok(Insert::Replace),
+        _ => Err(format!("unknown insert position {}", position).into()),
+    }
+}
+
+impl SirixApi {
+    fn check_writable(&self, method: &str) -> Result<(), Box<EvalAltResult>> {
+        match self.read_only {
+            true => Err(format!("sirix.{} is not allowed in read-only mode", method).into()),
+            false => Ok(()),
+        }
+    }
+
+    /// Run a method changing a resource and append it to the audit log,
+    /// like the commands of the shell which change data.
+    fn audited(
+        &self,
+        method: &str,
+        database: &str,
+        resource: &str,
+        nodekey: Option<i64>,
+        call: impl FnOnce() -> ScriptResult,
+    ) -> ScriptResult {
+        reset_status();
+        let result = call();
+        let command = format!(
+            "{}: sirix.{}(\"{}\", \"{}\")",
+            self.origin, method, database, resource
+        );
+        let target = Target {
+            database: Some(database.to_owned()),
+            resource: Some(resource.to_owned()),
+            nodekey: nodekey.map(i128::from),
+            server: None,
+        };
+        if let Err(err) = audit::record_call(&self.session, command, target, result.is_err()) {
+            outln!("{}", err);
+        }
+        result
+    }
+
+    fn read(&mut self, database: &str, resource: &str, options: Map) -> ScriptResult {
+        let options = read_options(&options)?;
+        let resource = self
+            .sirix
+            .json_database(database.to_owned())
+            .resource(resource.to_owned());
+        json_result(interruptible(move || {
+            read_json_resource(
+                resource,
+                options.nodekey,
+                options.revision,
+                options.max_depth,
+                options.limit,
+                options.skip,
+                None,
+            )
+        }))
+    }
+
+    fn read_xml(&mut self, database: &str, resource: &str, options: Map) -> ScriptResult {
+        let options = read_options(&options)?;
+        let resource = self
+            .sirix
+            .xml_database(database.to_owned())
+            .resource(resource.to_owned());
+        xml_result(interruptible(move || {
+            read_xml_resource(
+                resource,
+                options.nodekey,
+                options.revision,
+                options.max_depth,
+                options.limit,
+                options.skip,
+            )
+        }))
+    }
+
+    fn query(&mut self, query_string: &str) -> ScriptResult {
+        let sirix = self.sirix.clone();
+        let query_string = query_string.to_owned();
+        json_result(interruptible(move || {
+            query(sirix, query_string, None, None)
+        }))
+    }
+
+    fn query_resource(
+        &mut self,
+        database: &str,
+        resource: &str,
+        query_string: &str,
+    ) -> ScriptResult {
+        let resource = self
+            .sirix
+            .json_database(database.to_owned())
+            .resource(resource.to_owned());
+        let query_string = query_string.to_owned();
+        json_result(interruptible(move || {
+            query_json_resource(resource, query_string, None, None)
+        }))
+    }
+
+    fn create(&mut self, database: &str, resource: &str, data: Dynamic) -> ScriptResult {
+        self.check_writable("create")?;
+        let data = match data.clone().try_cast::<String>() {
+            Some(data) => data,
+            None => {
+                let value: Value = rhai::serde::from_dynamic(&data)?;
+                value.to_string()
+            }
+        };
+        let handle = self
+            .sirix
+            .json_database(database.to_owned())
+            .resource(resource.to_owned());
+        self.audited("create", database, resource, None, || {
+            json_result(interruptible(move || create_json_resource(handle, data)))
+        })
+    }
+
+    fn create_xml(&mut self, database: &str, resource: &str, data: &str) -> ScriptResult {
+        self.check_writable("create_xml")?;
+        let handle = self
+            .sirix
+            .xml_database(database.to_owned())
+            .resource(resource.to_owned());
+        let data = data.to_owned();
+        self.audited("create_xml", database, resource, None, || {
+            xml_result(interruptible(move || create_xml_resource(handle, data)))
+        })
+    }
+
+    fn update(
+        &mut self,
+        database: &str,
+        resource: &str,
+        nodekey: i64,
+        data: Dynamic,
+        position: &str,
+    ) -> ScriptResult {
+        self.check_writable("update")?;
+        let insert = insert_position(position)?;
+        let data = match data.clone().try_cast::<String>() {
+            Some(data) => data,
+            None => {
+                let value: Value = rhai::serde::from_dynamic(&data)?;
+                value.to_string()
+            }
+        };
+        let handle = self
+            .sirix
+            .json_database(database.to_owned())
+            .resource(resource.to_owned());
+        self.audited("update", database, resource, Some(nodekey), || {
+            json_result(interruptible(move || {
+                update_json_resource(handle, nodekey as u128, data, insert, None)
+            }))
+        })
+    }
+
+    fn update_xml(
+        &mut self,
+        database: &str,
+        resource: &str,
+        nodekey: i64,
+        data: &str,
+        position: &str,
+    ) -> ScriptResult {
+        self.check_writable("update_xml")?;
+        let insert = insert_position(position)?;
+        let handle = self
+            .sirix
+            .xml_database(database.to_owned())
+            .resource(resource.to_owned());
+        let data = data.to_owned();
+        self.audited("update_xml", database, resource, Some(nodekey), || {
+            xml_result(interruptible(move || {
+                update_xml_resource(handle, nodekey as u128, data, insert, None)
+            }))
+        })
+    }
+
+    fn history(&mut self, database: &str, resource: &str) -> ScriptResult {
+        let resource = self
+            .sirix
+            .json_database(database.to_owned())
+            .resource(resource.to_owned());
+        json_result(interruptible(move || resource_history(resource)))
+    }
+
+    fn diff(&mut self, database: &str, resource: &str, first: i64, second: i64) -> ScriptResult {
+        let resource = self
+            .sirix
+            .json_database(database.to_owned())
+            .resource(resource.to_owned());
+        json_result(interruptible(move || {
+            diff_json_resource(resource, first as u64, second as u64, None, None)
+        }))
+    }
+}
+
+fn create_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .register_type_with_name::<SirixApi>("Sirix")
+        .register_fn("read", |api: &mut SirixApi, db: &str, res: &str| {
+            api.read(db, res, Map::new())
+        })
+        .register_fn("read", SirixApi::read)
+        .register_fn("read_xml", |api: &mut SirixApi, db: &str, res: &str| {
+            api.read_xml(db, res, Map::new())
+        })
+        .register_fn("read_xml", SirixApi::read_xml)
+        .register_fn("query", SirixApi::query)
+        .register_fn("query", SirixApi::query_resource)
+        .register_fn("create", SirixApi::create)
+        .register_fn("create_xml", SirixApi::create_xml)
+        .register_fn(
+            "update",
+            |api: &mut SirixApi, db: &str, res: &str, nodekey: i64, data: Dynamic| {
+                api.update(db, res, nodekey, data, "child")
+            },
+        )
+        .register_fn("update", SirixApi::update)
+        .register_fn(
+            "update_xml",
+            |api: &mut SirixApi, db: &str, res: &str, nodekey: i64, data: &str| {
+                api.update_xml(db, res, nodekey, data, "child")
+            },
+        )
+        .register_fn("update_xml", SirixApi::update_xml)
+        .register_fn("history", SirixApi::history)
+        .register_fn("diff", SirixApi::diff);
+    engine
+}
+
+fn new_scope(sirix: Sirix, context: &ContextStruct, origin: String) -> Scope<'static> {
+    let mut scope = Scope::new();
+    let api = SirixApi {
+        sirix,
+        read_only: context.read_only,
+        session: Session::new(context),
+        origin,
+    };
+    scope.push_constant("sirix", api);
+    scope
+}
+
+/// Run a script file with the `sirix` bindings.
+pub fn run_file(path: &str, sirix: Sirix, context: &ContextStruct) -> Result<(), String> {
+    let mut scope = new_scope(sirix, context, format!("script {}", path));
+    create_engine()
+        .run_file_with_scope(&mut scope, PathBuf::from(path))
+        .map_err(|err| err.to_string())
+}
+
+/// Evaluate a script expression, returning its value unless it is `()`.
+pub fn eval(code: &str, sirix: Sirix, context: &ContextStruct) -> Result<Option<Value>, String> {
+    let mut scope = new_scope(sirix, context, "eval".to_owned());
+    let result = create_engine()
+        .eval_with_scope::<Dynamic>(&mut scope, code)
+        .map_err(|err| err.to_string())?;
+    if result.is::<()>() {
+        return Ok(None);
+    }
+    rhai::serde::from_dynamic(&result)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
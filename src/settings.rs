@@ -0,0 +1,57 @@
+use crate::http::trace::set_trace;
+
+/// The names accepted by `set`; any other name defines a variable.
+pub const SETTINGS: &[&str] = &["trace", "timing", "prompt"];
+
+/// Shell settings changed with `set <name> <value>`.
+#[derive(Debug, Default)]
+pub struct Settings {
+    /// Log every HTTP request and response.
+    pub trace: bool,
+    /// Print timing and response statistics after each command.
+    pub timing: bool,
+    /// The prompt template - the built in prompt is used if not set.
+    pub prompt: Option<String>,
+}
+
+fn parse_switch(value: Option<&str>, current: bool) -> Result<bool, String> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(!current),
+    };
+    match value {
+        "on" | "true" => Ok(true),
+        "off" | "false" => Ok(false),
+        _ => Err(format!("expected on or off, got {}", value)),
+    }
+}
+
+fn switch_name(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+impl Settings {
+    /// Change a setting. Switches are toggled if no value is given.
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<(), String> {
+        match name {
+            "trace" => {
+                self.trace = parse_switch(value, self.trace)?;
+                set_trace(self.trace);
+            }
+            "timing" => self.timing = parse_switch(value, self.timing)?,
+            "prompt" => self.prompt = value.map(|value| value.to_owned()),
+            _ => return Err(format!("unknown setting {}", name)),
+        }
+        Ok(())
+    }
+
+    pub fn print(&self) {
+        outln!("trace: {}", switch_name(self.trace));
+        outln!("timing: {}", switch_name(self.timing));
+        outln!("prompt: {}", self.prompt.as_deref().unwrap_or("default"));
+    }
+}
@@ -0,0 +1,50 @@
+use sirix_rust_client::synchronous::sirix::Sirix;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{config::config_dir, parsers::ContextStruct};
+
+/// Guards against files that (indirectly) source themselves.
+const MAX_DEPTH: usize = 16;
+
+static DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Run every line of `path` as a shell command. Empty lines and lines
+/// starting with `#` are skipped, and reading stops at `exit` or `quit`.
+pub fn source_file(path: &Path, sirix: Sirix, context: &mut ContextStruct) -> Result<(), String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("cannot read {}: {}", path.display(), err))?;
+    if DEPTH.fetch_add(1, Ordering::SeqCst) >= MAX_DEPTH {
+        DEPTH.fetch_sub(1, Ordering::SeqCst);
+        return Err(format!("{}: files sourced too deeply", path.display()));
+    }
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if matches!(line, "exit" | "quit") {
+            break;
+        }
+        if let Err(err) = crate::run_line(line, sirix.clone(), context) {
+            outln!("{}:{}: {}", path.display(), number + 1, err);
+        }
+    }
+    DEPTH.fetch_sub(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Run `~/.sirixshrc` followed by the rc file of the connection profile,
+/// `~/.sirixsh/rc/<profile>`, skipping those that do not exist.
+pub fn source_startup_files(profile: &str, sirix: Sirix, context: &mut ContextStruct) {
+    let home_rc = dirs::home_dir().map(|home| home.join(".sirixshrc"));
+    let profile_rc = config_dir().map(|dir| dir.join("rc").join(profile));
+    for path in home_rc.iter().chain(profile_rc.iter()) {
+        if path.is_file() {
+            if let Err(err) = source_file(path, sirix.clone(), context) {
+                outln!("{}", err);
+            }
+        }
+    }
+}
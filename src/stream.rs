@@ -0,0 +1,204 @@
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+
+use crate::{
+    http::{connection::StreamArgs, trace, Connection},
+    interrupt, metadata, output,
+    parsers::{Context, ReadOpts},
+    timing,
+};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Where a streamed read is written: the command output or a file, which
+/// gets a progress display on stderr.
+enum Sink {
+    /// Whether the output written so far ends with a newline.
+    Output(bool),
+    File(BufWriter<File>),
+}
+
+impl Sink {
+    fn open(path: &Option<String>) -> Result<Sink, String> {
+        match path {
+            Some(path) => File::create(path)
+                .map(|file| Sink::File(BufWriter::new(file)))
+                .map_err(|err| format!("cannot create {}: {}", path, err)),
+            None => Ok(Sink::Output(true)),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        match self {
+            Sink::Output(newline) => {
+                output::write_raw(bytes);
+                *newline = bytes.last().map_or(*newline, |last| *last == b'\n');
+                Ok(())
+            }
+            Sink::File(file) => file.write_all(bytes).map_err(|err| err.to_string()),
+        }
+    }
+
+    fn progress(&self, bytes: u64) {
+        if let Sink::File(_) = self {
+            eprint!("\r{}", format_bytes(bytes));
+            let _ = io::stderr().flush();
+        }
+    }
+
+    fn finish(self, bytes: u64) -> Result<(), String> {
+        match self {
+            Sink::Output(newline) => {
+                if !newline {
+                    output::write_raw(b"\n");
+                }
+                // a recording keeps a placeholder rather than the document
+                output::capture(&format!("<streamed {} bytes>", bytes));
+                Ok(())
+            }
+            Sink::File(mut file) => {
+                eprintln!("\r{}", format_bytes(bytes));
+                file.flush().map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        bytes if bytes >= 1 << 30 => format!("{:.1} GiB", bytes as f64 / (1u64 << 30) as f64),
+        bytes if bytes >= 1 << 20 => format!("{:.1} MiB", bytes as f64 / (1u64 << 20) as f64),
+        bytes if bytes >= 1 << 10 => format!("{:.1} KiB", bytes as f64 / (1u64 << 10) as f64),
+        bytes => format!("{} bytes", bytes),
+    }
+}
+
+fn args(opts: &ReadOpts) -> StreamArgs {
+    StreamArgs {
+        node_id: opts.nodekey,
+        revision: opts.revision.clone(),
+        max_level: opts.max_depth,
+        top_level_limit: opts.limit,
+        top_level_skip_last_node: opts.skip,
+        metadata: match opts.metadata.as_deref() {
+            Some("all") => Some("true"),
+            Some("key") => Some("nodeKey"),
+            Some(_) => Some("nodeKeyAndChildCount"),
+            None => None,
+        },
+    }
+}
+
+/// Copy the body of a single read to the sink as it arrives.
+fn copy_body(
+    connection: &Connection,
+    opts: &ReadOpts,
+    (database, resource, db_type): (&str, &str, &str),
+    sink: &mut Sink,
+) -> Result<u64, String> {
+    let response = connection.read(database, resource, db_type, &args(opts))?;
+    let sized = response.header("content-length").is_some();
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut bytes = 0;
+    loop {
+        if interrupt::interrupted() {
+            return Err(format!("stopped after {}", format_bytes(bytes)));
+        }
+        let read = reader.read(&mut buffer).map_err(|err| err.to_string())?;
+        if read == 0 {
+            if !sized {
+                trace::count_body(bytes);
+            }
+            return Ok(bytes);
+        }
+        sink.write(&buffer[..read])?;
+        bytes += read as u64;
+        sink.progress(bytes);
+    }
+}
+
+/// Read the top level nodes `chunk_size` at a time, writing each as a line
+/// of NDJSON. Only one chunk is held in memory at a time.
+fn copy_chunks(
+    connection: &Connection,
+    opts: &ReadOpts,
+    (database, resource): (&str, &str),
+    chunk_size: u64,
+    sink: &mut Sink,
+) -> Result<(u64, u64), String> {
+    let mut args = args(opts);
+    args.top_level_limit = Some(chunk_size);
+    args.metadata = Some("nodeKey");
+    let (mut bytes, mut nodes) = (0, 0);
+    loop {
+        if interrupt::interrupted() {
+            return Err(format!("stopped after {} nodes", nodes));
+        }
+        let response = connection.read(database, resource, "json", &args)?;
+        let sized = response.header("content-length").is_some();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .read_to_end(&mut body)
+            .map_err(|err| err.to_string())?;
+        if !sized {
+            trace::count_body(body.len() as u64);
+        }
+        let page: Value =
+            serde_json::from_slice(&body).map_err(|err| format!("invalid response: {}", err))?;
+        let chunk = metadata::top_level(&page);
+        for (_, node) in &chunk {
+            let mut line = serde_json::to_vec(node).unwrap();
+            line.push(b'\n');
+            sink.write(&line)?;
+            bytes += line.len() as u64;
+        }
+        nodes += chunk.len() as u64;
+        sink.progress(bytes);
+        match chunk.last() {
+            Some((Some(key), _)) if chunk.len() as u64 == chunk_size => {
+                args.top_level_skip_last_node = Some(*key)
+            }
+            _ => return Ok((bytes, nodes)),
+        }
+    }
+}
+
+/// Read a resource without holding the whole document in memory, copying
+/// it to the output or to `--output`, or paging through its top level nodes
+/// as NDJSON with `--chunk-size`. The resource is that of the options or
+/// of the resource context.
+pub fn read(connection: &Connection, opts: &ReadOpts, context: &Context) -> Result<(), String> {
+    let (database, resource, db_type) = opts.target(context)?;
+    if opts.chunk_size.is_some() && db_type == "xml" {
+        return Err("--chunk-size only works with JSON resources".to_owned());
+    }
+    let mut sink = Sink::open(&opts.output)?;
+    interrupt::reset();
+    let (bytes, nodes) = match opts.chunk_size {
+        Some(chunk_size) => copy_chunks(
+            connection,
+            opts,
+            (&database, &resource),
+            chunk_size.max(1),
+            &mut sink,
+        )?,
+        None => (
+            copy_body(
+                connection,
+                opts,
+                (&database, &resource, &db_type),
+                &mut sink,
+            )?,
+            0,
+        ),
+    };
+    timing::record_stream(nodes);
+    sink.finish(bytes)?;
+    if let Some(path) = &opts.output {
+        outln!("wrote {} to {}", format_bytes(bytes), path);
+    }
+    Ok(())
+}
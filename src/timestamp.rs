@@ -0,0 +1,66 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone};
+
+/// Parse a timestamp in RFC 3339, e.g. `2021-06-01T12:00:00.5+02:00`, or a
+/// date and time or just a date without an offset, e.g. `2021-06-01T12:00:00`
+/// or `2021-06-01`, which is taken to be in `zone`. Timestamps compare by the
+/// instant they denote, whatever their offset or precision.
+pub fn parse<Tz: TimeZone>(value: &str, zone: &Tz) -> Option<DateTime<FixedOffset>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp);
+    }
+    let naive = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+    })?;
+    let timestamp = zone.from_local_datetime(&naive).earliest()?;
+    Some(timestamp.with_timezone(&timestamp.offset().fix()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn offsets_denote_the_same_instant() {
+        assert_eq!(
+            parse("2021-06-01T12:00:00+02:00", &Utc),
+            parse("2021-06-01T10:00:00Z", &Utc)
+        );
+    }
+
+    #[test]
+    fn precision_does_not_matter() {
+        assert_eq!(
+            parse("2021-06-01T10:00:00.000Z", &Utc),
+            parse("2021-06-01T10:00:00Z", &Utc)
+        );
+        assert!(parse("2021-06-01T10:00:00.5Z", &Utc) > parse("2021-06-01T10:00:00Z", &Utc));
+    }
+
+    #[test]
+    fn without_an_offset_in_the_zone() {
+        let zone = FixedOffset::east_opt(3600).unwrap();
+        assert_eq!(
+            parse("2021-06-01T11:00:00", &zone),
+            parse("2021-06-01T10:00:00Z", &Utc)
+        );
+        assert_eq!(
+            parse("2021-06-01", &Utc),
+            parse("2021-06-01T00:00:00Z", &Utc)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("yesterday", &Utc), None);
+    }
+}
@@ -0,0 +1,96 @@
+use quick_xml::{events::Event, Reader};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::http::trace::{received, round_trips};
+
+static ITEMS: AtomicU64 = AtomicU64::new(0);
+
+/// Count the top level items of a JSON response. Metadata responses wrap the
+/// data in a `value` field, which is counted instead.
+pub fn count_json_items(value: &Value) -> u64 {
+    match value {
+        Value::Object(object) => match (object.get("metadata"), object.get("value")) {
+            (Some(_), Some(value)) => count_json_items(value),
+            _ => object.len() as u64,
+        },
+        Value::Array(array) => array.len() as u64,
+        _ => 1,
+    }
+}
+
+/// Count the children of the root element of an XML response.
+pub fn count_xml_items(xml: &str) -> u64 {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    let mut items = 0;
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(_)) => {
+                if depth == 1 {
+                    items += 1;
+                }
+                depth += 1;
+            }
+            Ok(Event::Empty(_)) if depth == 1 => items += 1,
+            Ok(Event::End(_)) => depth -= 1,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    items
+}
+
+/// Record the items of a JSON response for the statistics of the running
+/// command. The bytes received are counted by the HTTP middleware.
+pub fn record_json(value: &Value) {
+    ITEMS.fetch_add(count_json_items(value), Ordering::SeqCst);
+}
+
+/// Record the items of an XML response for the statistics of the running
+/// command.
+pub fn record_xml(xml: &str) {
+    ITEMS.fetch_add(count_xml_items(xml), Ordering::SeqCst);
+}
+
+/// Record the top level nodes of a streamed response.
+pub fn record_stream(items: u64) {
+    ITEMS.fetch_add(items, Ordering::SeqCst);
+}
+
+/// Measures a single command for `set timing on`.
+pub struct Timer {
+    start: Instant,
+    round_trips: u64,
+    received: (u64, u64),
+}
+
+impl Timer {
+    pub fn start() -> Timer {
+        ITEMS.store(0, Ordering::SeqCst);
+        Timer {
+            start: Instant::now(),
+            round_trips: round_trips(),
+            received: received(),
+        }
+    }
+
+    pub fn report(&self) {
+        let (bytes, unsized_) = received();
+        let unknown = match unsized_.saturating_sub(self.received.1) {
+            0 => String::new(),
+            responses => format!(" (and {} responses of unknown length)", responses),
+        };
+        outln!(
+            "Time: {:.3} ms, round trips: {}, bytes received: {}{}, items: {}",
+            self.start.elapsed().as_secs_f64() * 1000.0,
+            round_trips() - self.round_trips,
+            bytes - self.received.0,
+            unknown,
+            ITEMS.load(Ordering::SeqCst)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::env;
+
+/// How many previous results are kept for `$_`, `$_1`, ...
+const MAX_RESULTS: usize = 10;
+
+/// Variables defined with `set <name> <value>` and the results of previous
+/// commands, interpolated into command arguments as `$name`.
+#[derive(Debug, Default)]
+pub struct Variables {
+    variables: BTreeMap<String, String>,
+    results: VecDeque<Value>,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+/// Follow a dotted path such as `metadata.nodeKey` or `value.0` into a result.
+/// Keys missing from a metadata response are looked up in its `metadata`
+/// object, so `$_.nodeKey` works for reads with `--metadata`.
+pub fn lookup<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    let (key, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some(value),
+    };
+    let child = match value {
+        Value::Object(object) => object.get(*key).or_else(|| {
+            object
+                .get("metadata")
+                .and_then(|metadata| metadata.get(*key))
+        }),
+        Value::Array(array) => key.parse::<usize>().ok().and_then(|i| array.get(i)),
+        _ => None,
+    }?;
+    lookup(child, rest)
+}
+
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        _ => value.to_string(),
+    }
+}
+
+impl Variables {
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.variables.insert(name.to_owned(), value.to_owned());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.variables.get(name)
+    }
+
+    /// Remember the result of a command as `$_`, shifting older ones to `$_1`,
+    /// `$_2` and so on.
+    pub fn push_result(&mut self, value: Value) {
+        self.results.push_front(value);
+        self.results.truncate(MAX_RESULTS);
+    }
+
+    /// The result of the command `back` commands ago, `0` being the last one.
+    pub fn result(&self, back: usize) -> Option<&Value> {
+        self.results.get(back)
+    }
+
+    fn resolve(&self, name: &str) -> Result<String, String> {
+        let path: Vec<&str> = name.split('.').collect();
+        if path[0] == "env" && path.len() == 2 {
+            return env::var(path[1]).map_err(|_| format!("${}: not set", name));
+        }
+        if let Some(back) = path[0].strip_prefix('_') {
+            let back = match back {
+                "" => 0,
+                back => back
+                    .parse::<usize>()
+                    .map_err(|_| format!("${}: undefined variable", name))?,
+            };
+            let result = self
+                .result(back)
+                .ok_or_else(|| format!("${}: no such result", path[0]))?;
+            return lookup(result, &path[1..])
+                .map(render)
+                .ok_or_else(|| format!("${}: not found in result", name));
+        }
+        match self.variables.get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(format!("${}: undefined variable", name)),
+        }
+    }
+
+    /// Replace every `$name` in `arg`. `$$` stands for a literal `$`.
+    pub fn interpolate(&self, arg: &str) -> Result<String, String> {
+        let mut interpolated = String::new();
+        let mut rest = arg;
+        while let Some(start) = rest.find('$') {
+            interpolated.push_str(&rest[..start]);
+            rest = &rest[start + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                interpolated.push('$');
+                rest = after;
+                continue;
+            }
+            let end = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+            // a trailing dot ends the sentence rather than starting a path
+            let name = rest[..end].trim_end_matches('.');
+            if name.is_empty() {
+                interpolated.push('$');
+                continue;
+            }
+            interpolated.push_str(&self.resolve(name)?);
+            rest = &rest[name.len()..];
+        }
+        interpolated.push_str(rest);
+        Ok(interpolated)
+    }
+
+    pub fn print(&self) {
+        for (name, value) in &self.variables {
+            outln!("{} = {}", name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn variables() -> Variables {
+        let mut variables = Variables::default();
+        variables.set("db", "orders");
+        variables.push_result(json!({"value": [{"id": 7}], "metadata": {"nodeKey": 3}}));
+        variables.push_result(json!("latest"));
+        variables
+    }
+
+    #[test]
+    fn interpolate_variables() {
+        assert_eq!(
+            variables().interpolate("--database=$db"),
+            Ok("--database=orders".to_owned())
+        );
+    }
+
+    #[test]
+    fn interpolate_results_and_paths() {
+        let variables = variables();
+        assert_eq!(variables.interpolate("$_"), Ok("latest".to_owned()));
+        assert_eq!(variables.interpolate("$_1.value.0.id"), Ok("7".to_owned()));
+        // keys missing from a metadata response are looked up in `metadata`
+        assert_eq!(variables.interpolate("$_1.nodeKey"), Ok("3".to_owned()));
+    }
+
+    #[test]
+    fn interpolate_literal_dollars_and_trailing_dots() {
+        let variables = variables();
+        assert_eq!(
+            variables.interpolate("$$db costs $"),
+            Ok("$db costs $".to_owned())
+        );
+        assert_eq!(
+            variables.interpolate("in $db."),
+            Ok("in orders.".to_owned())
+        );
+    }
+
+    #[test]
+    fn interpolate_errors() {
+        let variables = variables();
+        assert_eq!(
+            variables.interpolate("$nope"),
+            Err("$nope: undefined variable".to_owned())
+        );
+        assert_eq!(
+            variables.interpolate("$_5"),
+            Err("$_5: no such result".to_owned())
+        );
+        assert_eq!(
+            variables.interpolate("$_1.missing"),
+            Err("$_1.missing: not found in result".to_owned())
+        );
+    }
+}